@@ -7,28 +7,270 @@
 //
 // CREATED:         06/16/2022
 //
-// LAST EDITED:     06/19/2022
+// LAST EDITED:     10/19/2026
 ////
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use axum_database_sessions::AxumSession;
-use axum::{extract::Query, http::StatusCode, Json};
+use axum::{
+    body::{self, Full, StreamBody}, extract::{Path, Query},
+    http::{header::{self, HeaderMap, HeaderName}, Method, StatusCode},
+    response::Response, routing::{on, MethodFilter}, Json, Router,
+};
+use futures_util::{stream, StreamExt};
 use model;
 use oauth2::AccessToken;
 use reqwest_middleware::ClientWithMiddleware;
+use serde::Deserialize;
+use tokio::sync::Semaphore;
 use tracing::{event, Level};
 use crate::REDDIT_BASE;
+use crate::SCOPES_KEY;
 use crate::USER_AGENT;
-use crate::extractor;
-use crate::rate_limit::RateLimiter;
+use crate::USERNAME_KEY;
+use crate::duplicates::Identifier;
+use crate::extraction_cache::{ExtractionCache, ExtractionCacheStats};
+use crate::extractors::{ExtractError, Registry};
+use crate::filter_store::FilterStore;
+use crate::media_proxy::{self, MediaProxy};
+use crate::ordering::{
+    self, OrderedListings, OrderedPage, DEFAULT_PAGE_SIZE,
+};
+use crate::rate_limit::{Priority, RateLimiter};
+use crate::response_cache::{CachedResponse, ResponseCache};
+use crate::validation::Validator;
+use crate::retry::CircuitOpen;
+
+// Most posts a client may ask about in one /video/batch request.
+const MAX_BATCH_SIZE: usize = 25;
+
+// Most extractions run at once for a single /video/batch request.
+const MAX_BATCH_CONCURRENCY: usize = 4;
+
+// Most posts that may be identified at once: a full page of a listing.
+const MAX_IDENTITY_BATCH_SIZE: usize = 100;
+
+// Most thumbnails fetched at once for a single /duplicates request.
+const MAX_IDENTITY_CONCURRENCY: usize = 8;
+
+// Largest response that's buffered to be cached. A page of a listing is at
+// most 100 posts, which is well under this.
+const MAX_CACHED_BODY: usize = 4 * 1024 * 1024;
+
+// Upstream response headers that are relayed to the client.
+const FORWARDED_HEADERS: &[&'static str] = &[
+    "cache-control", "content-length", "etag", "last-modified",
+    "x-ratelimit-remaining", "x-ratelimit-reset", "x-ratelimit-used",
+];
+
+// Query parameters of listings, for paging through them.
+const LISTING_QUERY: &[&'static str] = &[
+    "after", "before", "count", "limit", "sr_detail",
+];
+
+// Listings that can be limited to a time range.
+const TOP_QUERY: &[&'static str] = &[
+    "after", "before", "count", "limit", "sr_detail", "t",
+];
+
+const SUBMITTED_QUERY: &[&'static str] = &[
+    "after", "before", "count", "limit", "sr_detail", "sort", "t",
+];
+
+const SEARCH_QUERY: &[&'static str] = &[
+    "after", "before", "count", "limit", "sr_detail", "q", "sort", "t",
+    "type", "restrict_sr",
+];
+
+// Which page of an ordered compilation to hand out.
+#[derive(Deserialize)]
+pub struct PageQuery {
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+
+// A Reddit API endpoint that is exposed through the proxy.
+pub struct ProxiedEndpoint {
+    // HTTP method accepted from the client and used for the upstream request.
+    pub method: Method,
+
+    // Axum path pattern. Captures (":name") are substituted into the upstream
+    // path, which is otherwise identical.
+    pub path: &'static str,
+
+    // Query parameters relayed upstream. Anything else is dropped.
+    pub query: &'static [&'static str],
+
+    // OAuth scope the session must have been granted.
+    pub scope: &'static str,
+
+    // Scheduling class of the upstream request in the rate limiter.
+    pub priority: Priority,
+
+    // Whether responses are coalesced and cached. They're buffered whole to
+    // do that, so it's only for endpoints whose responses are small.
+    pub cached: bool,
+}
+
+// Every Reddit endpoint the frontend is allowed to reach.
+pub const PROXIED_ENDPOINTS: &[ProxiedEndpoint] = &[
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/api/v1/me",
+        query: &[],
+        scope: "identity",
+        priority: Priority::Interactive,
+        cached: true,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/user/:username/saved",
+        query: LISTING_QUERY,
+        scope: "history",
+        priority: Priority::Interactive,
+        cached: true,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/user/:username/upvoted",
+        query: LISTING_QUERY,
+        scope: "history",
+        priority: Priority::Interactive,
+        cached: true,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/user/:username/submitted",
+        query: SUBMITTED_QUERY,
+        scope: "history",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/r/:subreddit/hot",
+        query: LISTING_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/r/:subreddit/new",
+        query: LISTING_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/r/:subreddit/top",
+        query: TOP_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/r/:subreddit/rising",
+        query: LISTING_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/user/:username/m/:multireddit/hot",
+        query: LISTING_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/user/:username/m/:multireddit/new",
+        query: LISTING_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/user/:username/m/:multireddit/top",
+        query: TOP_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/user/:username/m/:multireddit/rising",
+        query: LISTING_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/search",
+        query: SEARCH_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::GET,
+        path: "/r/:subreddit/search",
+        query: SEARCH_QUERY,
+        scope: "read",
+        priority: Priority::Interactive,
+        cached: false,
+    },
+    ProxiedEndpoint {
+        method: Method::POST,
+        path: "/api/unsave",
+        query: &["id"],
+        scope: "save",
+        priority: Priority::Background,
+        cached: false,
+    },
+];
+
+async fn get_user_token(session: &AxumSession) ->
+    Result<AccessToken, StatusCode>
+{
+    session.get("token").await.ok_or(StatusCode::UNAUTHORIZED)
+}
+
+// The OAuth scopes granted to the session. Sessions created before scopes
+// were recorded have to log in again to find out what they've been granted.
+async fn get_granted_scopes(session: &AxumSession) ->
+    Result<Vec<String>, StatusCode>
+{
+    session.get(SCOPES_KEY).await.ok_or(StatusCode::UNAUTHORIZED)
+}
+
+// The Reddit username of the logged in user, for settings that are kept
+// across sessions.
+async fn get_username(session: &AxumSession) -> Result<String, StatusCode> {
+    session.get(USERNAME_KEY).await.ok_or(StatusCode::UNAUTHORIZED)
+}
+
+// Opaque identifier of the logged in user, for tying things to them.
+async fn get_user_id(session: &AxumSession) -> Result<String, StatusCode> {
+    let token = get_user_token(session).await?;
+    Ok(media_proxy::user_id(token.secret()))
+}
 
 async fn get_user_client(session: &AxumSession) ->
     Result<ClientWithMiddleware, StatusCode>
 {
     // Initialize a reqwest client for this session
-    let token: AccessToken = session.get("token").await
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let token = get_user_token(session).await?;
 
     let mut headers = reqwest::header::HeaderMap::new();
     let auth = "bearer ".to_string() + token.secret().as_str();
@@ -53,61 +295,548 @@ async fn get_user_client(session: &AxumSession) ->
     Ok(client)
 }
 
+// Status code for a request that could not be completed upstream.
+fn upstream_error(error: reqwest_middleware::Error) -> StatusCode {
+    event!(Level::ERROR, "{:?}", error);
+    match error {
+        reqwest_middleware::Error::Middleware(e)
+            if e.downcast_ref::<CircuitOpen>().is_some() =>
+            StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+// The subset of the upstream headers that is relayed to the client: the
+// content type and the headers in FORWARDED_HEADERS.
+fn forwarded_headers(upstream_headers: &HeaderMap) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(content_type) = upstream_headers.get(header::CONTENT_TYPE) {
+        headers.insert(header::CONTENT_TYPE, content_type.clone());
+    }
+    for name in FORWARDED_HEADERS {
+        if let Some(value) = upstream_headers.get(*name) {
+            headers.insert(HeaderName::from_static(name), value.clone());
+        }
+    }
+    headers
+}
+
+// Convert the upstream response into one for the client, preserving the
+// status code and forwarded headers. The body is streamed through as it
+// arrives, so if the client hangs up, dropping the response body also aborts
+// the upstream transfer.
+fn relay_response(response: reqwest::Response) -> Result<Response, StatusCode>
+{
+    let mut builder = Response::builder().status(response.status());
+    builder.headers_mut().unwrap()
+        .extend(forwarded_headers(response.headers()));
+    builder
+        .body(body::boxed(StreamBody::new(response.bytes_stream())))
+        .map_err(|e| {
+            event!(Level::ERROR, "{:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+// Read the whole upstream response so that it can be shared or cached.
+// Responses larger than MAX_CACHED_BODY are refused rather than held.
+async fn buffer_response(response: reqwest::Response) ->
+    Result<CachedResponse, StatusCode>
+{
+    let status = response.status();
+    let headers = forwarded_headers(response.headers());
+    let mut body = Vec::new();
+    let mut chunks = response.bytes_stream();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk.map_err(|e| {
+            event!(Level::ERROR, "{:?}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+        if body.len() + chunk.len() > MAX_CACHED_BODY {
+            event!(Level::ERROR, "response too large to cache");
+            return Err(StatusCode::BAD_GATEWAY);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(CachedResponse { status, headers, body: body.into() })
+}
+
+fn replay_response(cached: CachedResponse) -> Result<Response, StatusCode> {
+    let mut builder = Response::builder().status(cached.status);
+    builder.headers_mut().unwrap().extend(cached.headers);
+    builder
+        .body(body::boxed(Full::from(cached.body)))
+        .map_err(|e| {
+            event!(Level::ERROR, "{:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// ProxiedEndpoint
+////
+
+// Whether the value may be put in an upstream path as one segment: the name
+// of a user, subreddit or multireddit. Path captures arrive percent-decoded,
+// so anything else could add segments ("../") or a query ("?").
+pub fn is_path_name(value: &str) -> bool {
+    !value.is_empty() && value.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl ProxiedEndpoint {
+    // Substitute path captures into the pattern to get the upstream path, or
+    // None if a capture isn't a name.
+    fn reddit_path(&self, captures: &HashMap<String, String>) ->
+        Option<String>
+    {
+        self.path.split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(name) => captures.get(name).map(|value| value.as_str())
+                    .filter(|value| is_path_name(value)),
+                None => Some(segment),
+            })
+            .collect::<Option<Vec<&str>>>()
+            .map(|segments| segments.join("/"))
+    }
+
+    async fn handle(
+        &'static self, captures: HashMap<String, String>,
+        mut params: HashMap<String, String>, session: AxumSession,
+        rate_limiter: RateLimiter, cache: ResponseCache,
+    ) -> Result<Response, StatusCode>
+    {
+        let scopes = get_granted_scopes(&session).await?;
+        if !scopes.iter().any(|scope| scope == self.scope) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        params.retain(|name, _| self.query.contains(&name.as_str()));
+        let path = self.reddit_path(&captures)
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        match self.method {
+            Method::POST => proxy_reddit_post(
+                path, Query(params), session, rate_limiter, self.priority,
+                cache).await,
+            _ => proxy_reddit_get(
+                path, Query(params), session, rate_limiter, self.priority,
+                self.cached.then_some(cache)).await,
+        }
+    }
+
+    // Register this endpoint's handler on the router.
+    pub fn route(
+        &'static self, router: Router, rate_limiter: RateLimiter,
+        cache: ResponseCache,
+    ) -> Router
+    {
+        let filter = match self.method {
+            Method::POST => MethodFilter::POST,
+            _ => MethodFilter::GET,
+        };
+        router.route(self.path, on(filter, move |
+            Path(captures): Path<HashMap<String, String>>,
+            Query(params): Query<HashMap<String, String>>,
+            session: AxumSession,
+        | {
+            self.handle(captures, params, session, rate_limiter, cache)
+        }))
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 // Public API
 ////
 
+// Responses are streamed through, unless there's a cache for them.
 pub async fn proxy_reddit_get(
     reddit_endpoint: String, Query(params): Query<HashMap<String, String>>,
-    session: AxumSession, mut rate_limiter: RateLimiter,
-) -> Result<String, StatusCode>
+    session: AxumSession, mut rate_limiter: RateLimiter, priority: Priority,
+    cache: Option<ResponseCache>,
+) -> Result<Response, StatusCode>
 {
     let client = get_user_client(&session).await?;
-    let response = rate_limiter.send(
-        client.get(REDDIT_BASE.to_string() + &reddit_endpoint)
-            .query(&params)
-    )
-        .await
-        .map_err(|e| {
-            event!(Level::ERROR, "{:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-    Ok(response.text().await.map_err(|e| {
-        event!(Level::ERROR, "{:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?)
+    let url = REDDIT_BASE.to_string() + &reddit_endpoint;
+    let cache = match cache.filter(ResponseCache::is_enabled) {
+        Some(cache) => cache,
+        None => {
+            let request = client.get(&url).query(&params);
+            let response = rate_limiter.send(&client, request, priority)
+                .await
+                .map_err(upstream_error)?;
+            return relay_response(response);
+        },
+    };
+
+    // Identical requests from the same user share one upstream request, and
+    // the response is reused until it expires.
+    let user = get_user_id(&session).await?;
+    let query: BTreeMap<&String, &String> = params.iter().collect();
+    let key = format!("{}?{:?}", url, query);
+    let cached = cache.get_or_fetch(&user, &key, || async move {
+        let request = client.get(&url).query(&params);
+        let response = rate_limiter.send(&client, request, priority)
+            .await
+            .map_err(upstream_error)?;
+        buffer_response(response).await
+    }).await?;
+    replay_response(cached)
 }
 
 pub async fn proxy_reddit_post(
     reddit_endpoint: String, Query(params): Query<HashMap<String, String>>,
-    session: AxumSession, mut rate_limiter: RateLimiter,
-) -> Result<String, StatusCode>
+    session: AxumSession, mut rate_limiter: RateLimiter, priority: Priority,
+    cache: ResponseCache,
+) -> Result<Response, StatusCode>
 {
     let client = get_user_client(&session).await?;
     let response = rate_limiter.send(
+        &client,
         client.post(REDDIT_BASE.to_string() + &reddit_endpoint)
-            .query(&params)
+            .query(&params),
+        priority,
     )
+        .await
+        .map_err(upstream_error)?;
+
+    // Anything we've cached for this user may have been changed by this.
+    if response.status().is_success() {
+        cache.invalidate_user(&get_user_id(&session).await?);
+    }
+    relay_response(response)
+}
+
+// Register every endpoint in PROXIED_ENDPOINTS on the router.
+pub fn proxy_routes(
+    router: Router, rate_limiter: RateLimiter, cache: ResponseCache,
+) -> Router
+{
+    PROXIED_ENDPOINTS.iter().fold(router, |router, endpoint| {
+        endpoint.route(router, rate_limiter.clone(), cache.clone())
+    })
+}
+
+// The set of OAuth scopes needed by PROXIED_ENDPOINTS.
+pub fn required_scopes() -> Vec<&'static str> {
+    let mut scopes: Vec<&'static str> = PROXIED_ENDPOINTS.iter()
+        .map(|endpoint| endpoint.scope)
+        .collect();
+    scopes.sort();
+    scopes.dedup();
+    scopes
+}
+
+// Everything the /video endpoints need.
+#[derive(Clone)]
+pub struct MediaService {
+    pub registry: Arc<Registry>,
+    pub validator: Option<Arc<Validator>>,
+    pub proxy: Option<MediaProxy>,
+    pub filters: FilterStore,
+}
+
+impl MediaService {
+    // Resolve the media behind a post and pick a source to the client's
+    // preference, making sure it's playable if we've been asked to. Posts
+    // the user's filter excludes aren't resolved, and media it excludes
+    // isn't validated.
+    async fn resolve(
+        &self, request: model::PostMediaRequest,
+        preference: &model::QualityPreference,
+        filter: Option<&model::Filter>,
+    ) -> Result<model::MediaDescriptor, ExtractError>
+    {
+        if let (Some(filter), Some(facts)) = (filter, &request.facts) {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs_f64())
+                .unwrap_or(0.0);
+            filter.check(facts, now)
+                .map_err(|e| ExtractError::Filtered(e.to_string()))?;
+        }
+        let mut media = self.registry.get_url(request).await?;
+        if let Some(filter) = filter {
+            filter.check_media(&media)
+                .map_err(|e| ExtractError::Filtered(e.to_string()))?;
+        }
+        media.select(preference);
+        if let Some(validator) = &self.validator {
+            validator.validate(&mut media).await?;
+        }
+        Ok(media)
+    }
+
+    // The logged in user's filter, if they've configured one.
+    async fn filter(&self, session: &AxumSession) ->
+        Option<Arc<model::Filter>>
+    {
+        let username = get_username(session).await.ok()?;
+        self.filters.get(&username)
+    }
+
+    // Point the media's URLs at the proxy where necessary.
+    fn rewrite(&self, media: &mut model::MediaDescriptor, user: &str) {
+        if let Some(proxy) = &self.proxy {
+            proxy.rewrite(media, user);
+        }
+    }
+}
+
+fn extract_error_status(error: &ExtractError) -> StatusCode {
+    match error {
+        ExtractError::Unsupported | ExtractError::Unplayable(_)
+            | ExtractError::Filtered(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        ExtractError::NotFound => StatusCode::NOT_FOUND,
+        ExtractError::Malformed(_) | ExtractError::Upstream(_) =>
+            StatusCode::BAD_GATEWAY,
+    }
+}
+
+pub async fn get_video_url(
+    Query(preference): Query<model::QualityPreference>,
+    session: AxumSession, Json(request): Json<model::PostMediaRequest>,
+    service: MediaService,
+) -> Result<Json<model::MediaDescriptor>, (StatusCode, String)>
+{
+    // Resolving makes requests to whatever URL we're given, so it's only
+    // done for users we know.
+    let user = get_user_id(&session).await
+        .map_err(|status| (status, String::new()))?;
+    let filter = service.filter(&session).await;
+    let mut media = service.resolve(request, &preference, filter.as_deref())
         .await
         .map_err(|e| {
             event!(Level::ERROR, "{:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
+            (extract_error_status(&e), e.to_string())
         })?;
-    Ok(response.text().await.map_err(|e| {
-        event!(Level::ERROR, "{:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?)
+    service.rewrite(&mut media, &user);
+    Ok(Json(media))
 }
 
-pub async fn get_video_url(Json(request): Json<model::MediaUrlRequest>) ->
-    Result<String, StatusCode>
+// Resolve the media behind several posts at once, so the client can fetch
+// ahead of what it's playing. One post failing doesn't fail the batch.
+pub async fn get_video_batch(
+    Query(preference): Query<model::QualityPreference>,
+    session: AxumSession,
+    Json(requests): Json<Vec<model::PostMediaRequest>>,
+    service: MediaService,
+) -> Result<Json<Vec<model::MediaResult>>, (StatusCode, String)>
 {
-    extractor::get_url(request)
-        .await
-        .map_err(|e| {
+    if requests.len() > MAX_BATCH_SIZE {
+        return Err((StatusCode::PAYLOAD_TOO_LARGE, format!(
+            "at most {} posts may be requested at once", MAX_BATCH_SIZE)));
+    }
+    let user = get_user_id(&session).await
+        .map_err(|status| (status, String::new()))?;
+
+    let filter = service.filter(&session).await;
+    let semaphore = Arc::new(Semaphore::new(MAX_BATCH_CONCURRENCY));
+    let tasks = requests.into_iter().map(|request| {
+        let service = service.clone();
+        let filter = filter.clone();
+        let preference = preference.clone();
+        let semaphore = semaphore.clone();
+        let url = request.request.url.clone();
+        let task = tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            service.resolve(request, &preference, filter.as_deref()).await
+        });
+        (url, task)
+    }).collect::<Vec<_>>();
+
+    let mut results = Vec::new();
+    for (url, task) in tasks {
+        let result = task.await.map_err(|e| {
             event!(Level::ERROR, "{:?}", e);
-            StatusCode::BAD_REQUEST
-        })
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+        })?;
+        results.push(match result {
+            Ok(mut media) => {
+                service.rewrite(&mut media, &user);
+                model::MediaResult { url, media: Some(media), error: None }
+            },
+            Err(e) => {
+                event!(Level::DEBUG, "{}: {:?}", url, e);
+                model::MediaResult {
+                    url, media: None, error: Some(e.to_string()),
+                }
+            },
+        });
+    }
+    Ok(Json(results))
+}
+
+// Stream media from a host the browser can't load it from itself.
+pub async fn get_media(
+    Path(token): Path<String>, headers: HeaderMap, session: AxumSession,
+    proxy: MediaProxy,
+) -> Result<Response, StatusCode>
+{
+    let user = get_user_id(&session).await?;
+    proxy.fetch(&token, &user, &headers).await
+}
+
+// Fetch every post of the compilation, put them in order, and hand out the
+// first page. Later pages are handed out by get_ordered_page, so the order
+// stays the same however long the user takes to get through it.
+pub async fn create_ordered(
+    Query(page): Query<PageQuery>, session: AxumSession,
+    Json(request): Json<model::OrderedRequest>, rate_limiter: RateLimiter,
+    listings: OrderedListings,
+) -> Result<Json<OrderedPage>, StatusCode>
+{
+    let scopes = get_granted_scopes(&session).await?;
+    let permitted = request.sources.iter().all(|source| {
+        let scope = ordering::required_scope(source);
+        scopes.iter().any(|granted| granted == scope)
+    });
+    if !permitted {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let client = get_user_client(&session).await?;
+    let user = get_user_id(&session).await?;
+    let page_size = page.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    Ok(Json(listings.create(&client, rate_limiter, &user, request, page_size)
+            .await?))
+}
+
+pub async fn get_ordered_page(
+    Path(cursor): Path<String>, Query(page): Query<PageQuery>,
+    session: AxumSession, listings: OrderedListings,
+) -> Result<Json<OrderedPage>, StatusCode>
+{
+    let user = get_user_id(&session).await?;
+    let page_size = page.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    Ok(Json(listings.page(&cursor, &user, page.offset, page_size)?))
+}
+
+// Identify the media of the posts, so the client can tell which of them are
+// of the same clip.
+pub async fn identify_posts(
+    session: AxumSession, Json(requests): Json<Vec<model::IdentityRequest>>,
+    identifier: Identifier,
+) -> Result<Json<Vec<model::MediaIdentity>>, (StatusCode, String)>
+{
+    get_user_id(&session).await
+        .map_err(|status| (status, String::new()))?;
+    if requests.len() > MAX_IDENTITY_BATCH_SIZE {
+        return Err((StatusCode::PAYLOAD_TOO_LARGE, format!(
+            "at most {} posts may be identified at once",
+            MAX_IDENTITY_BATCH_SIZE)));
+    }
+
+    let identities = stream::iter(requests)
+        .map(|request| identifier.identify(request))
+        .buffered(MAX_IDENTITY_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+    Ok(Json(identities))
+}
+
+// The logged in user's filter rules. Users that haven't configured any get
+// the defaults, which don't filter anything.
+pub async fn get_filters(session: AxumSession, store: FilterStore) ->
+    Result<Json<model::FilterRules>, StatusCode>
+{
+    let username = get_username(&session).await?;
+    Ok(Json(store.get(&username)
+            .map(|filter| filter.rules().clone())
+            .unwrap_or_default()))
+}
+
+pub async fn put_filters(
+    session: AxumSession, Json(rules): Json<model::FilterRules>,
+    store: FilterStore,
+) -> Result<StatusCode, (StatusCode, String)>
+{
+    let username = get_username(&session).await
+        .map_err(|status| (status, String::new()))?;
+    let filter = model::Filter::new(rules)
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    store.set(&username, filter).await.map_err(|e| {
+        event!(Level::ERROR, "{:?}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_extraction_stats(cache: ExtractionCache) ->
+    Json<ExtractionCacheStats>
+{
+    Json(cache.stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(path: &str) -> &'static ProxiedEndpoint {
+        PROXIED_ENDPOINTS.iter().find(|endpoint| endpoint.path == path)
+            .unwrap()
+    }
+
+    fn captures(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn only_small_responses_are_cached() {
+        let cached = PROXIED_ENDPOINTS.iter()
+            .filter(|endpoint| endpoint.cached)
+            .map(|endpoint| endpoint.path)
+            .collect::<Vec<_>>();
+        assert_eq!(cached, ["/api/v1/me", "/user/:username/saved",
+                            "/user/:username/upvoted"]);
+    }
+
+    // The first chunk reaches the client while the rest of the upstream
+    // response is still on its way.
+    #[tokio::test]
+    async fn uncached_responses_are_streamed() {
+        let (sender, receiver) = tokio::sync::mpsc::channel::<
+                Result<&'static [u8], std::io::Error>>(1);
+        let chunks = stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|chunk| (chunk, receiver))
+        });
+        let upstream = axum::http::Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(reqwest::Body::wrap_stream(chunks))
+            .unwrap();
+        let mut response = relay_response(upstream.into()).unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE],
+                   "application/json");
+
+        sender.send(Ok(b"{\"kind\":")).await.unwrap();
+        let first = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            hyper::body::HttpBody::data(response.body_mut()),
+        ).await.expect("first chunk was held back").unwrap().unwrap();
+        assert_eq!(&first[..], b"{\"kind\":");
+        drop(sender);
+    }
+
+    #[test]
+    fn captures_are_substituted() {
+        let endpoint = endpoint("/user/:username/m/:multireddit/top");
+        let path = endpoint.reddit_path(&captures(&[
+            ("username", "some_user-1"), ("multireddit", "clips"),
+        ]));
+        assert_eq!(path.as_deref(), Some("/user/some_user-1/m/clips/top"));
+    }
+
+    #[test]
+    fn captures_that_leave_the_segment_are_refused() {
+        // "/user/..%2F..%2Fapi%2Fv1%2Fme%2Fprefs%3F/saved", as axum decodes
+        // the capture.
+        let endpoint = endpoint("/user/:username/saved");
+        for username in ["../../api/v1/me/prefs?", "..", "a?b", "a/b", ""] {
+            let path = endpoint.reddit_path(
+                &captures(&[("username", username)]));
+            assert_eq!(path, None, "{:?} should be refused", username);
+        }
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -7,9 +7,10 @@
 //
 // CREATED:         06/03/2022
 //
-// LAST EDITED:     06/11/2022
+// LAST EDITED:     10/19/2026
 ////
 
+use std::collections::HashMap;
 use std::fs::File;
 use std::io;
 use serde::{Serialize, Deserialize};
@@ -19,6 +20,64 @@ pub struct Configuration {
     pub listen_address: String,
     pub script_name: Option<String>,
     pub hostname: String,
+
+    // Seconds to keep successful responses of the cached Reddit endpoints.
+    // Zero disables caching.
+    pub response_cache_ttl: Option<u64>,
+
+    pub extraction_cache: Option<ExtractionCacheConfiguration>,
+
+    // Serve media through /media. Disabled if absent.
+    pub media_proxy: Option<MediaProxyConfiguration>,
+
+    // Check extracted media before returning it. Disabled if absent.
+    pub media_validation: Option<MediaValidationConfiguration>,
+
+    pub filters: Option<FilterConfiguration>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct ExtractionCacheConfiguration {
+    // Seconds to keep extracted media. Zero disables caching.
+    pub ttl: Option<u64>,
+
+    // Seconds to keep failures, e.g. for unsupported hosts.
+    pub negative_ttl: Option<u64>,
+
+    // Seconds to keep extracted media, per host of the post URL. Hosts whose
+    // media URLs are signed need a TTL shorter than the signature's lifetime.
+    #[serde(default)]
+    pub host_ttl: HashMap<String, u64>,
+
+    // File to keep the cache in across restarts.
+    pub path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MediaProxyConfiguration {
+    // Hosts (of the media, not the post) whose media is proxied, e.g. hosts
+    // that refuse hotlinking. Subdomains are included. There's no wildcard:
+    // proxying every host would make the proxy an open relay.
+    pub hosts: Vec<String>,
+
+    // Seconds a /media URL stays valid after it's handed out.
+    pub token_ttl: Option<u64>,
+
+    // Bytes per second each user may stream through the proxy.
+    pub bandwidth_limit: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MediaValidationConfiguration {
+    // Bytes. Larger media is rejected in favour of a smaller rendition.
+    pub max_size: Option<u64>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct FilterConfiguration {
+    // File to keep users' filter rules in. They're lost on restart if
+    // absent.
+    pub path: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -0,0 +1,328 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            duplicates.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Identifies the media of posts, so that the same clip
+//                  posted several times can be played once.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use image::{imageops::FilterType, DynamicImage};
+use model::{IdentityRequest, MediaIdentity, ThumbnailHash};
+use reqwest::{Client, Url};
+use tokio::time::Duration;
+use tracing::{event, Level};
+use crate::public_address;
+use crate::USER_AGENT;
+
+// How long to wait for a thumbnail.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Thumbnails are only fetched from Reddit's own image hosts. Subdomains are
+// included.
+const THUMBNAIL_HOSTS: &[&'static str] = &["redd.it", "redditmedia.com"];
+
+// Thumbnails are small. Anything bigger than this isn't one.
+const MAX_THUMBNAIL_SIZE: usize = 1024 * 1024;
+
+// Hashes are remembered by thumbnail URL, up to this many.
+const MAX_CACHED_HASHES: usize = 10_000;
+
+// Computes the identities of posts' media, remembering the hashes of the
+// thumbnails it's seen.
+#[derive(Clone)]
+pub struct Identifier {
+    client: Client,
+    hashes: Arc<Mutex<HashMap<String, ThumbnailHash>>>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Canonical URLs
+////
+
+fn file_stem(file: &str) -> &str {
+    file.split('.').next().unwrap_or(file)
+}
+
+// The query of the URL without tracking parameters, which can be all that
+// tells two links apart ("watch?v=...", "view.php?id=...").
+fn query(url: &Url) -> Option<String> {
+    let pairs = url.query_pairs()
+        .filter(|(name, _)| !name.starts_with("utm_"))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    if pairs.is_empty() {
+        return None;
+    }
+    let mut url = url.clone();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+    url.query().map(str::to_string)
+}
+
+// The URL with everything that doesn't change which media it leads to
+// removed: the scheme, "www.", tracking parameters, file extensions of
+// hosts that serve one file in several formats, and so on. It's not a URL
+// anymore, just something to compare.
+pub fn canonical_url(url: &str) -> Option<String> {
+    let url = Url::parse(url.trim()).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    let host = host.trim_start_matches("www.").trim_start_matches("m.");
+    let segments = url.path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty())
+             .collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let youtube = |id: &str| format!("youtube.com/watch?v={}", id);
+    let canonical = match (host, segments.as_slice()) {
+        ("youtu.be", [id]) | ("youtube.com", ["shorts", id]) => youtube(id),
+        ("youtube.com", ["watch"]) => youtube(&url.query_pairs()
+            .find(|(name, _)| name == "v")?.1),
+        ("imgur.com", [file]) | ("i.imgur.com", [file]) =>
+            format!("imgur.com/{}", file_stem(file)),
+        ("v.redd.it", [id, ..]) => format!("v.redd.it/{}", id),
+
+        // Previews are named after the image they're of.
+        ("i.redd.it", [file]) | ("preview.redd.it", [file]) =>
+            format!("i.redd.it/{}", file_stem(file)),
+        ("gfycat.com", [.., name]) => format!(
+            "gfycat.com/{}",
+            name.split('-').next().unwrap_or(name).to_lowercase()),
+        _ => {
+            let key = format!("{}/{}", host, segments.join("/"));
+            match query(&url) {
+                Some(query) => key + "?" + &query,
+                None => key,
+            }
+        },
+    };
+    Some(canonical)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Thumbnail hashes
+////
+
+fn is_thumbnail_host(url: &Url) -> bool {
+    let host = match url.host_str() {
+        Some(host) => host.to_lowercase(),
+        None => return false,
+    };
+    THUMBNAIL_HOSTS.iter().any(|name| {
+        host == *name || host.ends_with(&(".".to_string() + name))
+    })
+}
+
+// Difference hash: shrink the image to 9x8 and record, for each pixel,
+// whether it's brighter than its neighbour to the right. It survives
+// scaling and recompression, which is how copies of a clip usually differ.
+fn difference_hash(image: &DynamicImage) -> ThumbnailHash {
+    let small = image.grayscale()
+        .resize_exact(9, 8, FilterType::Triangle)
+        .to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    ThumbnailHash(hash)
+}
+
+impl Identifier {
+    pub fn new() -> reqwest::Result<Self> {
+        // Thumbnail URLs come from listings, so they're only followed to
+        // public addresses on the thumbnail hosts, redirects included.
+        let client = public_address::client_builder()
+            .redirect(public_address::redirect_policy(is_thumbnail_host))
+            .user_agent(USER_AGENT)
+            .timeout(TIMEOUT)
+            .build()?;
+        Ok(Self { client, hashes: Arc::new(Mutex::new(HashMap::new())) })
+    }
+
+    async fn fetch_thumbnail(&self, url: &Url) ->
+        Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>
+    {
+        let response = self.client.get(url.clone()).send().await?
+            .error_for_status()?;
+        if response.content_length().unwrap_or(0) as usize
+            > MAX_THUMBNAIL_SIZE
+        {
+            return Err("thumbnail too large".into());
+        }
+        let body = response.bytes().await?;
+        if body.len() > MAX_THUMBNAIL_SIZE {
+            return Err("thumbnail too large".into());
+        }
+        Ok(body.to_vec())
+    }
+
+    // The hash of the thumbnail, if it can be had. Failures aren't
+    // remembered, since they're usually transient.
+    async fn thumbnail_hash(&self, url: &str) -> Option<ThumbnailHash> {
+        if let Some(hash) = self.hashes.lock().unwrap().get(url) {
+            return Some(*hash);
+        }
+        let parsed = Url::parse(url).ok().filter(is_thumbnail_host)?;
+        let body = match self.fetch_thumbnail(&parsed).await {
+            Ok(body) => body,
+            Err(e) => {
+                event!(Level::DEBUG, "{}: {:?}", url, e);
+                return None;
+            },
+        };
+
+        // Decoding is too slow for the async workers.
+        let hash = tokio::task::spawn_blocking(move || {
+            image::load_from_memory(&body).map(|image| difference_hash(&image))
+        }).await.ok()?;
+        let hash = match hash {
+            Ok(hash) => hash,
+            Err(e) => {
+                event!(Level::DEBUG, "{}: {:?}", url, e);
+                return None;
+            },
+        };
+
+        let mut hashes = self.hashes.lock().unwrap();
+        if hashes.len() >= MAX_CACHED_HASHES {
+            hashes.clear();
+        }
+        hashes.insert(url.to_string(), hash);
+        Some(hash)
+    }
+
+    pub async fn identify(&self, request: IdentityRequest) -> MediaIdentity {
+        let thumbnail = match &request.thumbnail {
+            Some(url) => self.thumbnail_hash(url).await,
+            None => None,
+        };
+        MediaIdentity {
+            url: request.url.as_deref().and_then(canonical_url),
+            origin: request.crosspost_parent
+                .unwrap_or_else(|| request.name.clone()),
+            name: request.name,
+            thumbnail,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_links_keep_their_query() {
+        let first = canonical_url("https://example.com/view.php?id=1");
+        let second = canonical_url("https://www.example.com/view.php?id=2");
+        assert_eq!(first.as_deref(), Some("example.com/view.php?id=1"));
+        assert_ne!(first, second);
+        assert_eq!(
+            canonical_url("https://example.com/view.php?id=1&utm_source=x"),
+            first);
+    }
+
+    #[test]
+    fn hosts_that_serve_one_file_several_ways_are_canonical() {
+        let canonical = |url| canonical_url(url).unwrap();
+        assert_eq!(canonical("https://v.redd.it/k2x9qz0abcd81/DASH_720.mp4"),
+                   "v.redd.it/k2x9qz0abcd81");
+        assert_eq!(canonical("https://preview.redd.it/abc123.gif\
+                              ?format=mp4&s=0123abcd"),
+                   canonical("https://i.redd.it/abc123.gif"));
+        assert_eq!(canonical("https://i.imgur.com/AbC1234.gifv"),
+                   canonical("http://www.imgur.com/AbC1234"));
+        assert_eq!(canonical("https://youtu.be/dQw4w9WgXcQ"),
+                   canonical("https://m.youtube.com/watch\
+                              ?v=dQw4w9WgXcQ&t=42"));
+        assert_eq!(canonical("https://www.youtube.com/shorts/dQw4w9WgXcQ"),
+                   "youtube.com/watch?v=dQw4w9WgXcQ");
+        assert_eq!(canonical("https://gfycat.com/ifr/FirstSecondThird-tag"),
+                   "gfycat.com/firstsecondthird");
+        assert_eq!(canonical_url("not a url"), None);
+    }
+
+    // Brightness falls to the right in the top half, and rises in the
+    // bottom half.
+    fn halves(width: u32, height: u32, mirrored: bool) -> DynamicImage {
+        DynamicImage::ImageLuma8(image::GrayImage::from_fn(
+            width, height, |x, y| {
+                let x = match mirrored {
+                    true => width - 1 - x,
+                    false => x,
+                };
+                let falling = 255 - (x * 200 / width) as u8;
+                let rising = 55 + (x * 200 / width) as u8;
+                image::Luma([match y < height / 2 {
+                    true => falling,
+                    false => rising,
+                }])
+            }))
+    }
+
+    #[test]
+    fn hashes_survive_scaling() {
+        let hash = difference_hash(&halves(90, 80, false));
+        assert_eq!(hash, ThumbnailHash(0xffff_ffff_0000_0000));
+        assert_eq!(difference_hash(&halves(320, 180, false)), hash);
+        assert_eq!(hash.distance(&difference_hash(&halves(90, 80, true))),
+                   64);
+
+        let flat = DynamicImage::ImageLuma8(
+            image::GrayImage::from_pixel(90, 80, image::Luma([0])));
+        assert!(!difference_hash(&flat).is_distinctive());
+    }
+
+    fn identity(name: &str, url: Option<&str>, hash: Option<u64>) ->
+        MediaIdentity
+    {
+        MediaIdentity {
+            name: name.to_string(),
+            url: url.map(str::to_string),
+            origin: name.to_string(),
+            thumbnail: hash.map(ThumbnailHash),
+        }
+    }
+
+    #[test]
+    fn thumbnails_match_within_the_distance() {
+        let hash = 0x00ff_00ff_00ff_00ff;
+        let original = identity("t3_a", None, Some(hash));
+        let close = identity("t3_b", None, Some(hash ^ 0b11111));
+        let far = identity("t3_c", None, Some(hash ^ 0b111111));
+        assert!(close.is_duplicate_of(&original));
+        assert!(!close.is_copy_of(&original));
+        assert!(!far.is_duplicate_of(&original));
+
+        // Flat thumbnails look alike whatever the clip.
+        let black = identity("t3_d", None, Some(0));
+        assert!(!black.is_duplicate_of(&identity("t3_e", None, Some(0))));
+    }
+
+    #[test]
+    fn origins_and_urls_make_copies() {
+        let original = identity("t3_a", Some("v.redd.it/abc"), None);
+        let crosspost = MediaIdentity {
+            origin: "t3_a".to_string(),
+            ..identity("t3_b", None, None)
+        };
+        assert!(crosspost.is_copy_of(&original));
+        assert!(identity("t3_c", Some("v.redd.it/abc"), None)
+                .is_copy_of(&original));
+        assert!(!identity("t3_d", Some("v.redd.it/xyz"), None)
+                .is_duplicate_of(&original));
+        assert!(!identity("t3_e", None, None).is_duplicate_of(&original));
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
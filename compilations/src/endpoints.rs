@@ -7,7 +7,7 @@
 //
 // CREATED:         06/03/2022
 //
-// LAST EDITED:     06/16/2022
+// LAST EDITED:     10/19/2026
 ////
 
 use std::collections::HashMap;
@@ -16,12 +16,38 @@ use std::sync::Arc;
 use axum::{extract::Query, http::StatusCode, response::Redirect};
 use axum_database_sessions::AxumSession;
 use oauth2::{
-    AuthorizationCode, basic::BasicClient, CsrfToken,
+    AccessToken, AuthorizationCode, basic::BasicClient, CsrfToken,
     reqwest::{async_http_client}, Scope, TokenResponse,
 };
+use serde::Deserialize;
+use tracing::{event, Level};
 
+use crate::api::required_scopes;
 use crate::resolver::Resolver;
-use crate::CSRF_TOKEN_KEY;
+use crate::{
+    CSRF_TOKEN_KEY, REDDIT_BASE, SCOPES_KEY, USER_AGENT, USERNAME_KEY,
+};
+
+#[derive(Deserialize)]
+struct Identity {
+    name: String,
+}
+
+// The name of the Reddit user the token was issued to.
+async fn get_username(token: &AccessToken) ->
+    Result<String, Box<dyn std::error::Error + Send + Sync>>
+{
+    let response = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()?
+        .get(REDDIT_BASE.to_string() + "/api/v1/me")
+        .bearer_auth(token.secret())
+        .send()
+        .await?
+        .error_for_status()?;
+    let identity: Identity = serde_json::from_slice(&response.bytes().await?)?;
+    Ok(identity.name)
+}
 
 // Log the user into the application
 pub async fn login(session: AxumSession, client: Arc<BasicClient>) ->
@@ -29,9 +55,8 @@ pub async fn login(session: AxumSession, client: Arc<BasicClient>) ->
 {
     let (auth_url, csrf_token) = client
         .authorize_url(CsrfToken::new_random)
-        .add_scope(Scope::new("history".to_string()))
-        .add_scope(Scope::new("identity".to_string()))
-        .add_scope(Scope::new("save".to_string()))
+        .add_scopes(required_scopes().into_iter()
+                    .map(|scope| Scope::new(scope.to_string())))
         .url();
     session.set(CSRF_TOKEN_KEY, csrf_token).await;
 
@@ -65,6 +90,22 @@ pub async fn redirect_callback(
         .unwrap();
     session.set("token", token_result.access_token().clone()).await;
 
+    // If the server doesn't report the granted scopes, they're identical to
+    // the ones we requested.
+    let scopes: Vec<String> = match token_result.scopes() {
+        Some(scopes) => scopes.iter().map(|scope| scope.to_string()).collect(),
+        None => required_scopes().into_iter()
+            .map(|scope| scope.to_string())
+            .collect(),
+    };
+    session.set(SCOPES_KEY, scopes).await;
+
+    // Settings that are kept across sessions are tied to the username.
+    match get_username(token_result.access_token()).await {
+        Ok(username) => session.set(USERNAME_KEY, username).await,
+        Err(e) => event!(Level::WARN, "Couldn't get username: {:?}", e),
+    }
+
     // Route "/app" serves the wasm frontend application.
     Ok(Redirect::temporary(&resolver.get("app").unwrap()))
 }
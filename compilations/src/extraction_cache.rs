@@ -0,0 +1,270 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            extraction_cache.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Caches the results of media extraction, including failures.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use model::MediaDescriptor;
+use reqwest::Url;
+use serde::{Serialize, Deserialize};
+use tracing::{event, Level};
+use crate::configuration::ExtractionCacheConfiguration;
+use crate::extractors::ExtractError;
+
+// Seconds to keep extracted media, unless the host says otherwise.
+const DEFAULT_TTL: u64 = 6 * 60 * 60;
+
+// Seconds to remember that a URL is unsupported, or its media is gone.
+const DEFAULT_NEGATIVE_TTL: u64 = 10 * 60;
+
+// Seconds to remember failures that are likely to be transient: an
+// unreachable host, or a page we couldn't make sense of.
+const TRANSIENT_TTL: u64 = 60;
+
+// Hosts whose media URLs are signed and expire, with a TTL comfortably
+// shorter than their lifetime. Configured TTLs take precedence.
+const DEFAULT_HOST_TTLS: &[(&'static str, u64)] = &[
+    ("streamable.com", 30 * 60),
+    ("gfycat.com", 30 * 60),
+];
+
+// How often expired entries are dropped, and the cache is written to disk if
+// it's changed.
+const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+
+type ExtractResult = Result<MediaDescriptor, ExtractError>;
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Entry {
+    // Seconds since the epoch, so that entries survive a restart.
+    expires: u64,
+    result: ExtractResult,
+}
+
+struct Inner {
+    ttl: u64,
+    negative_ttl: u64,
+    host_ttls: HashMap<String, u64>,
+    path: Option<PathBuf>,
+    entries: Mutex<HashMap<String, Entry>>,
+
+    // Whether the entries have changed since they were last persisted.
+    dirty: AtomicBool,
+    hits: AtomicU64,
+    negative_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+// Extraction results, keyed by the URL of the post.
+#[derive(Clone)]
+pub struct ExtractionCache {
+    inner: Arc<Inner>,
+}
+
+// Response of the /video/stats endpoint.
+#[derive(Serialize)]
+pub struct ExtractionCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+
+    // Hits that returned a cached failure.
+    pub negative_hits: u64,
+    pub misses: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+impl ExtractionCache {
+    pub fn new(configuration: &ExtractionCacheConfiguration) -> Self {
+        let mut host_ttls = DEFAULT_HOST_TTLS.iter()
+            .map(|(host, ttl)| (host.to_string(), *ttl))
+            .collect::<HashMap<_, _>>();
+        host_ttls.extend(configuration.host_ttl.clone());
+        Self {
+            inner: Arc::new(Inner {
+                ttl: configuration.ttl.unwrap_or(DEFAULT_TTL),
+                negative_ttl: configuration.negative_ttl
+                    .unwrap_or(DEFAULT_NEGATIVE_TTL),
+                host_ttls,
+                path: configuration.path.as_ref().map(PathBuf::from),
+                entries: Mutex::new(HashMap::new()),
+                dirty: AtomicBool::new(false),
+                hits: AtomicU64::new(0),
+                negative_hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    // A zero TTL turns caching off.
+    pub fn is_enabled(&self) -> bool {
+        self.inner.ttl != 0
+    }
+
+    // The TTL for the result of extracting media from the URL. Failures are
+    // never kept longer than successes.
+    fn ttl(&self, url: &str, result: &ExtractResult) -> u64 {
+        let host = Url::parse(url).ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .unwrap_or_default();
+        let ttl = self.inner.host_ttls.iter()
+            .find(|(domain, _)| {
+                host == **domain || host.ends_with(&format!(".{}", domain))
+            })
+            .map(|(_, ttl)| *ttl)
+            .unwrap_or(self.inner.ttl);
+        match result {
+            Ok(_) => ttl,
+            Err(ExtractError::Unsupported) | Err(ExtractError::NotFound) =>
+                ttl.min(self.inner.negative_ttl),
+            Err(_) => ttl.min(self.inner.negative_ttl).min(TRANSIENT_TTL),
+        }
+    }
+
+    pub fn get(&self, url: &str) -> Option<ExtractResult> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let mut entries = self.inner.entries.lock().unwrap();
+        let result = match entries.get(url) {
+            Some(entry) if entry.expires > now() => Some(entry.result.clone()),
+            Some(_) => {
+                entries.remove(url);
+                self.inner.dirty.store(true, Ordering::Relaxed);
+                None
+            },
+            None => None,
+        };
+
+        let counter = match &result {
+            Some(Ok(_)) => &self.inner.hits,
+            Some(Err(_)) => &self.inner.negative_hits,
+            None => &self.inner.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    pub fn insert(&self, url: &str, result: &ExtractResult) {
+        // Host TTLs still apply when caching is off, so check for that
+        // first, or we'd store entries that get never returns.
+        if !self.is_enabled() {
+            return;
+        }
+        let ttl = self.ttl(url, result);
+        if ttl == 0 {
+            return;
+        }
+
+        let entry = Entry { expires: now() + ttl, result: result.clone() };
+        self.inner.entries.lock().unwrap().insert(url.to_string(), entry);
+        self.inner.dirty.store(true, Ordering::Relaxed);
+    }
+
+    // Drop the entries that have expired. Entries that are looked up are
+    // dropped by get, so this is for the ones nobody asks about again.
+    fn purge(&self) {
+        let mut entries = self.inner.entries.lock().unwrap();
+        let count = entries.len();
+        let now = now();
+        entries.retain(|_, entry| entry.expires > now);
+        if entries.len() != count {
+            self.inner.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn stats(&self) -> ExtractionCacheStats {
+        ExtractionCacheStats {
+            entries: self.inner.entries.lock().unwrap().len(),
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            negative_hits: self.inner.negative_hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    // Load the entries persisted by a previous run, if there are any.
+    pub async fn load(&self) -> io::Result<()> {
+        let path = match &self.inner.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let contents = match tokio::fs::read(path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let mut loaded: HashMap<String, Entry> =
+            serde_json::from_slice(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let now = now();
+        loaded.retain(|_, entry| entry.expires > now);
+        event!(Level::INFO, "Loaded {} extraction cache entries",
+               loaded.len());
+        self.inner.entries.lock().unwrap().extend(loaded);
+        Ok(())
+    }
+
+    // Write the entries to disk, if they've changed since the last time.
+    pub async fn persist(&self) -> io::Result<()> {
+        let path = match &self.inner.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if !self.inner.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        let contents = {
+            let entries = self.inner.entries.lock().unwrap();
+            serde_json::to_vec(&*entries)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        };
+
+        // Write to a temporary file first, so a crash never leaves a
+        // partially written cache behind.
+        let temporary = path.with_extension("tmp");
+        tokio::fs::write(&temporary, contents).await?;
+        tokio::fs::rename(&temporary, path).await
+    }
+
+    // Purge and persist the cache every PERSIST_INTERVAL, and log its
+    // statistics.
+    pub async fn spawn(self) {
+        let mut interval = tokio::time::interval(PERSIST_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.purge();
+            if let Err(e) = self.persist().await {
+                self.inner.dirty.store(true, Ordering::Relaxed);
+                event!(Level::ERROR, "{:?}", e);
+            }
+
+            let stats = self.stats();
+            event!(Level::DEBUG,
+                   "Extraction cache: {} entries, {} hits, {} negative hits, \
+                    {} misses", stats.entries, stats.hits,
+                   stats.negative_hits, stats.misses);
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,422 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            extractors.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Registry of media extractors, one per media host.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::fmt;
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaUrlRequest, PostMediaRequest};
+use reqwest::{Client, StatusCode, Url};
+use serde::{Serialize, Deserialize};
+use tracing::{event, Level};
+use crate::extraction_cache::ExtractionCache;
+use crate::extractor;
+use crate::public_address;
+use crate::USER_AGENT;
+
+mod direct;
+mod gifs;
+mod hls;
+mod imgur;
+mod opengraph;
+mod page;
+mod reddit_gallery;
+mod reddit_image;
+mod reddit_post;
+mod reddit_video;
+mod streamable;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ExtractError {
+    // The extractor doesn't know how to handle this particular URL.
+    Unsupported,
+
+    // The post or its media no longer exists.
+    NotFound,
+
+    // The media host returned something we couldn't make sense of.
+    Malformed(String),
+
+    // The media host couldn't be reached, or returned an error.
+    Upstream(String),
+
+    // None of the media's sources can be played, for the reasons given.
+    Unplayable(String),
+
+    // The media was excluded by the user's filter rules.
+    Filtered(String),
+}
+
+// Knows how to find the media behind links to one media host.
+#[async_trait]
+pub trait Extractor: Send + Sync {
+    // Short name, used in logs.
+    fn name(&self) -> &'static str;
+
+    // Whether this extractor should be tried for the URL.
+    fn matches(&self, url: &Url) -> bool;
+
+    async fn extract(&self, client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>;
+}
+
+// Dispatches each request to the first extractor that can handle it.
+pub struct Registry {
+    client: Client,
+    extractors: Vec<Box<dyn Extractor>>,
+
+    // Generic extractors, tried only once everything else has failed.
+    fallbacks: Vec<Box<dyn Extractor>>,
+    cache: Option<ExtractionCache>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// ExtractError
+////
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::Unsupported => write!(f, "unsupported media host"),
+            ExtractError::NotFound => write!(f, "media not found"),
+            ExtractError::Malformed(e) =>
+                write!(f, "malformed response: {}", e),
+            ExtractError::Upstream(e) => write!(f, "media host error: {}", e),
+            ExtractError::Unplayable(e) => write!(f, "unplayable: {}", e),
+            ExtractError::Filtered(e) => write!(f, "filtered: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+impl From<reqwest::Error> for ExtractError {
+    fn from(error: reqwest::Error) -> Self {
+        match error.status() {
+            Some(reqwest::StatusCode::NOT_FOUND) => ExtractError::NotFound,
+            _ => ExtractError::Upstream(error.to_string()),
+        }
+    }
+}
+
+// GET a text resource (a page, manifest or playlist) from a media host.
+async fn fetch_text(client: &Client, url: Url) ->
+    Result<String, ExtractError>
+{
+    // Addresses in URLs aren't resolved, so the client can't refuse them.
+    if !public_address::is_public_url(&url) {
+        return Err(ExtractError::Unsupported);
+    }
+    let response = client.get(url).send().await?;
+
+    // Some hosts answer with 403 or 410 for removed media rather than 404.
+    match response.status() {
+        StatusCode::FORBIDDEN | StatusCode::GONE =>
+            return Err(ExtractError::NotFound),
+        _ => {},
+    }
+    Ok(response.error_for_status()?.text().await?)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Registry
+////
+
+impl Registry {
+    pub fn new() -> reqwest::Result<Self> {
+        // Every URL we're asked about came from a user, so none of the
+        // requests made for them should reach anything of ours.
+        let client = public_address::client_builder()
+            .user_agent(USER_AGENT)
+            .build()?;
+        Ok(Self {
+            client,
+            extractors: Vec::new(),
+            fallbacks: Vec::new(),
+            cache: None,
+        })
+    }
+
+    // A registry containing every extractor we ship.
+    pub fn with_defaults() -> reqwest::Result<Self> {
+        let mut registry = Self::new()?;
+        registry
+            .register(reddit_video::RedditVideoExtractor)
+            .register(reddit_gallery::RedditGalleryExtractor)
+            .register(reddit_image::RedditImageExtractor)
+            .register(imgur::ImgurExtractor)
+            .register(streamable::StreamableExtractor)
+            .register(gifs::GiphyExtractor)
+            .register(gifs::GfycatExtractor)
+            .register(direct::DirectExtractor)
+            .register_fallback(opengraph::OpenGraphExtractor);
+        Ok(registry)
+    }
+
+    // Extractors are tried in the order they were registered.
+    pub fn register<E: Extractor + 'static>(&mut self, extractor: E) ->
+        &mut Self
+    {
+        self.extractors.push(Box::new(extractor));
+        self
+    }
+
+    // Fallbacks are tried in the order they were registered, after the
+    // original scraper.
+    pub fn register_fallback<E: Extractor + 'static>(&mut self, extractor: E)
+        -> &mut Self
+    {
+        self.fallbacks.push(Box::new(extractor));
+        self
+    }
+
+    pub fn set_cache(&mut self, cache: ExtractionCache) -> &mut Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    // Try each of the extractors that match the URL, until one of them
+    // supports it.
+    async fn try_extractors(&self, extractors: &[Box<dyn Extractor>],
+                            url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        for extractor in extractors.iter()
+            .filter(|extractor| extractor.matches(url))
+        {
+            match extractor.extract(&self.client, url).await {
+                Err(ExtractError::Unsupported) => continue,
+                result => {
+                    event!(Level::DEBUG, "{} handled {}", extractor.name(),
+                           url);
+                    return result;
+                },
+            }
+        }
+        Err(ExtractError::Unsupported)
+    }
+
+    pub async fn get_url(&self, request: PostMediaRequest) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let key = request.request.url.clone();
+        let cached = self.cache.as_ref().and_then(|cache| cache.get(&key));
+        let mut result = match cached {
+            Some(result) => result,
+            None => {
+                let result = self.extract(request.request).await;
+                if let Some(cache) = &self.cache {
+                    cache.insert(&key, &result);
+                }
+                result
+            },
+        };
+
+        // What's known about the post applies to cached media too, so it's
+        // not cached with it.
+        if let (true, Ok(media)) = (request.is_gif, result.as_mut()) {
+            reddit_video::mark_gif(media);
+        }
+        result
+    }
+
+    async fn extract(&self, mut request: MediaUrlRequest) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let mut url = Url::parse(&request.url)
+            .map_err(|_| ExtractError::Unsupported)?;
+
+        // Crossposts link to the post they were crossposted from, which is
+        // where the media is.
+        if reddit_post::is_post(&url) {
+            if let Some(original) =
+                reddit_post::original_url(&self.client, &url).await?
+            {
+                event!(Level::DEBUG, "{} links to {}", url, original);
+                request.url = original.to_string();
+                url = original;
+            }
+        }
+        match self.try_extractors(&self.extractors, &url).await {
+            Err(ExtractError::Unsupported) => {},
+            result => return result,
+        }
+
+        // Hosts without a dedicated extractor are still handled by the
+        // original scraper. If that fails too, see whether the page
+        // describes its media in a way we understand. The scraper has its
+        // own client, so it can only be trusted with public URLs.
+        if !public_address::is_public_url(&url) {
+            return Err(ExtractError::Unsupported);
+        }
+        let legacy = extractor::get_url(request).await;
+        match legacy {
+            Ok(url) => Ok(match Url::parse(&url) {
+                Ok(url) => direct::describe(&url),
+                Err(_) => MediaDescriptor::from_url(url),
+            }),
+            Err(e) => {
+                event!(Level::DEBUG, "{:?}", e);
+                self.try_extractors(&self.fallbacks, &url).await
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use super::*;
+
+    // Records that it was asked, and answers with whatever it was given.
+    struct Fake {
+        name: &'static str,
+        matches: bool,
+        result: Result<MediaDescriptor, ExtractError>,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl Extractor for Fake {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn matches(&self, _url: &Url) -> bool {
+            self.matches
+        }
+
+        async fn extract(&self, _client: &Client, _url: &Url) ->
+            Result<MediaDescriptor, ExtractError>
+        {
+            self.calls.lock().unwrap().push(self.name);
+            self.result.clone()
+        }
+    }
+
+    fn fake(
+        calls: &Arc<Mutex<Vec<&'static str>>>, name: &'static str,
+        matches: bool, result: Result<MediaDescriptor, ExtractError>,
+    ) -> Fake
+    {
+        Fake { name, matches, result, calls: calls.clone() }
+    }
+
+    fn media(url: &str) -> Result<MediaDescriptor, ExtractError> {
+        Ok(MediaDescriptor::from_url(url.to_string()))
+    }
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    // Names of the extractors that would be tried for the URL, in order.
+    fn candidates(extractors: &[Box<dyn Extractor>], url: &str) ->
+        Vec<&'static str>
+    {
+        let url = Url::parse(url).unwrap();
+        extractors.iter()
+            .filter(|extractor| extractor.matches(&url))
+            .map(|extractor| extractor.name())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn extractors_are_tried_in_registration_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = Registry::new().unwrap();
+        registry
+            .register(fake(&calls, "skipped", false, media("skipped")))
+            .register(fake(&calls, "declines", true,
+                           Err(ExtractError::Unsupported)))
+            .register(fake(&calls, "handles", true, media("handles")))
+            .register(fake(&calls, "too-late", true, media("too-late")));
+
+        let result = registry.try_extractors(
+            &registry.extractors, &url("https://example.com/a")).await;
+        assert_eq!(result.unwrap().primary().unwrap().url, "handles");
+        assert_eq!(*calls.lock().unwrap(), vec!["declines", "handles"]);
+    }
+
+    #[tokio::test]
+    async fn errors_other_than_unsupported_are_final() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = Registry::new().unwrap();
+        registry
+            .register(fake(&calls, "missing", true,
+                           Err(ExtractError::NotFound)))
+            .register(fake(&calls, "handles", true, media("handles")));
+
+        let result = registry.try_extractors(
+            &registry.extractors, &url("https://example.com/a")).await;
+        assert!(matches!(result, Err(ExtractError::NotFound)));
+        assert_eq!(*calls.lock().unwrap(), vec!["missing"]);
+    }
+
+    #[tokio::test]
+    async fn nothing_matching_is_unsupported() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = Registry::new().unwrap();
+        registry
+            .register(fake(&calls, "skipped", false, media("skipped")))
+            .register_fallback(fake(&calls, "fallback", true,
+                                    media("fallback")));
+
+        let url = url("https://example.com/a");
+        let result = registry.try_extractors(&registry.extractors, &url)
+            .await;
+        assert!(matches!(result, Err(ExtractError::Unsupported)));
+        assert!(calls.lock().unwrap().is_empty());
+
+        // Fallbacks are kept apart, for once everything else has failed.
+        let result = registry.try_extractors(&registry.fallbacks, &url)
+            .await;
+        assert_eq!(result.unwrap().primary().unwrap().url, "fallback");
+    }
+
+    #[test]
+    fn hosts_are_dispatched_to_their_extractors() {
+        let registry = Registry::with_defaults().unwrap();
+        let first = |url| candidates(&registry.extractors, url)
+            .first().copied();
+        assert_eq!(first("https://v.redd.it/abc123"), Some("v.redd.it"));
+        assert_eq!(first("https://imgur.com/a/abc123"), Some("imgur"));
+        assert_eq!(first("https://streamable.com/abc123"),
+                   Some("streamable"));
+
+        // Reddit's images are handled by their own extractor, even though
+        // they're files Direct would take too.
+        assert_eq!(candidates(&registry.extractors,
+                              "https://i.redd.it/abc123.jpg"),
+                   vec!["reddit image", "direct"]);
+    }
+
+    #[test]
+    fn files_on_other_hosts_fall_back_to_direct() {
+        let registry = Registry::with_defaults().unwrap();
+        assert_eq!(candidates(&registry.extractors,
+                              "https://example.com/clip.mp4"),
+                   vec!["direct"]);
+        assert!(candidates(&registry.fallbacks,
+                           "https://example.com/clip.mp4")
+                .contains(&"opengraph"));
+    }
+
+    #[test]
+    fn pages_on_other_hosts_fall_back_to_opengraph() {
+        let registry = Registry::with_defaults().unwrap();
+        let page = "https://example.com/watch/123";
+        assert!(candidates(&registry.extractors, page).is_empty());
+        assert_eq!(candidates(&registry.fallbacks, page), vec!["opengraph"]);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
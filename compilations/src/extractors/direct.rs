@@ -0,0 +1,117 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            direct.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Extractor for links that point straight at a media file.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaKind, MediaSource};
+use reqwest::{Client, Url};
+use super::{ExtractError, Extractor};
+
+pub struct DirectExtractor;
+
+// The MIME type of the video or image file at the URL, judging by its
+// extension.
+fn media_type(url: &Url) -> Option<String> {
+    let mime_type = mime_guess::from_path(url.path()).first()?;
+    match mime_type.type_() {
+        mime_guess::mime::VIDEO | mime_guess::mime::IMAGE =>
+            Some(mime_type.to_string()),
+        _ => None,
+    }
+}
+
+// Describe the file at the URL, as far as its extension allows.
+pub fn describe(url: &Url) -> MediaDescriptor {
+    let mime_type = media_type(url);
+    let kind = mime_type.as_deref().and_then(MediaKind::from_mime_type)
+        .unwrap_or(MediaKind::Video);
+    MediaDescriptor {
+        kind,
+        sources: vec![MediaSource {
+            mime_type,
+            ..MediaSource::new(url.to_string())
+        }],
+        looping: kind == MediaKind::Gif,
+        ..Default::default()
+    }
+}
+
+#[async_trait]
+impl Extractor for DirectExtractor {
+    fn name(&self) -> &'static str {
+        "direct"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        media_type(url).is_some()
+    }
+
+    async fn extract(&self, _client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        Ok(describe(url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe_str(url: &str) -> MediaDescriptor {
+        describe(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn videos_are_described_by_extension() {
+        let media = describe_str("https://example.com/clip.mp4");
+        assert_eq!(media.kind, MediaKind::Video);
+        assert!(!media.looping);
+        assert_eq!(media.sources.len(), 1);
+        assert_eq!(media.sources[0].url, "https://example.com/clip.mp4");
+        assert_eq!(media.sources[0].mime_type.as_deref(), Some("video/mp4"));
+    }
+
+    #[test]
+    fn gifs_loop() {
+        let media = describe_str("https://example.com/funny.gif");
+        assert_eq!(media.kind, MediaKind::Gif);
+        assert!(media.looping);
+        assert_eq!(media.sources[0].mime_type.as_deref(), Some("image/gif"));
+    }
+
+    #[test]
+    fn images_are_images() {
+        let media = describe_str("https://example.com/photo.JPG?width=640");
+        assert_eq!(media.kind, MediaKind::Image);
+        assert_eq!(media.sources[0].mime_type.as_deref(),
+                   Some("image/jpeg"));
+    }
+
+    #[test]
+    fn unknown_files_are_assumed_to_be_video() {
+        let media = describe_str("https://example.com/watch?v=123");
+        assert_eq!(media.kind, MediaKind::Video);
+        assert_eq!(media.sources[0].mime_type, None);
+        assert!(!DirectExtractor.matches(
+            &Url::parse("https://example.com/watch?v=123").unwrap()));
+    }
+
+    #[test]
+    fn only_media_files_match() {
+        let matches = |url| DirectExtractor.matches(&Url::parse(url).unwrap());
+        assert!(matches("https://example.com/clip.webm"));
+        assert!(matches("https://example.com/photo.png"));
+        assert!(!matches("https://example.com/page.html"));
+        assert!(!matches("https://example.com/playlist.m3u8"));
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,174 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            gifs.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Extractors for GIF hosting sites.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaKind, MediaSource};
+use reqwest::{Client, Url};
+use super::page::Page;
+use super::{fetch_text, ExtractError, Extractor};
+
+const GIPHY_HOSTS: &[&'static str] = &[
+    "giphy.com", "www.giphy.com", "media.giphy.com", "i.giphy.com",
+];
+
+// Hosts whose pages name the clip's MP4 file in their OpenGraph tags, the
+// way Gfycat's did.
+const SCRAPED_HOSTS: &[&'static str] = &[
+    "gfycat.com", "www.gfycat.com", "thumbs.gfycat.com",
+];
+
+// Giphy's MP4 files live at a predictable location, so no request is needed.
+pub struct GiphyExtractor;
+
+pub struct GfycatExtractor;
+
+fn has_host(url: &Url, hosts: &[&str]) -> bool {
+    url.host_str().map(|host| hosts.contains(&host)).unwrap_or(false)
+}
+
+// The ID of a Giphy clip, from its page (/gifs/some-title-ID) or from one of
+// its files (/media/ID/giphy.gif, or /media/v1.xyz/ID/giphy.gif).
+fn giphy_id(url: &Url) -> Option<String> {
+    let segments = url.path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        ["gifs", slug] | ["embed", slug] =>
+            slug.rsplit('-').next().map(|id| id.to_string()),
+        ["media", .., id, _file] => Some(id.to_string()),
+        [file] => file.split_once('.').map(|(id, _)| id.to_string()),
+        _ => None,
+    }
+}
+
+// The page of a clip, given the page itself or one of its files, e.g.
+// thumbs.gfycat.com/SomeName-size_restricted.gif
+fn gfycat_page(url: &Url) -> Option<Url> {
+    let name = url.path_segments()?.find(|segment| !segment.is_empty())?;
+    let name = name.split(|c| c == '-' || c == '.').next()?;
+    Url::parse(&format!("https://gfycat.com/{}", name)).ok()
+}
+
+pub fn parse_gfycat_page(base: &Url, html: &str) ->
+    Result<MediaDescriptor, ExtractError>
+{
+    let page = Page::parse(html);
+    let video = page.og_video(base).ok_or_else(|| ExtractError::Malformed(
+        "page has no og:video".to_string()))?;
+    Ok(MediaDescriptor {
+        kind: MediaKind::Gif,
+        looping: true,
+        sources: vec![video],
+        poster: page.og_image(base).map(|image| image.url),
+        ..Default::default()
+    })
+}
+
+#[async_trait]
+impl Extractor for GiphyExtractor {
+    fn name(&self) -> &'static str {
+        "giphy"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        has_host(url, GIPHY_HOSTS)
+    }
+
+    async fn extract(&self, _client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let id = giphy_id(url).ok_or(ExtractError::Unsupported)?;
+        let media = format!("https://i.giphy.com/media/{}", id);
+        Ok(MediaDescriptor {
+            kind: MediaKind::Gif,
+            looping: true,
+            sources: vec![MediaSource {
+                mime_type: Some("video/mp4".to_string()),
+                ..MediaSource::new(format!("{}/giphy.mp4", media))
+            }],
+            poster: Some(format!("{}/giphy_s.gif", media)),
+            ..Default::default()
+        })
+    }
+}
+
+#[async_trait]
+impl Extractor for GfycatExtractor {
+    fn name(&self) -> &'static str {
+        "gfycat"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        has_host(url, SCRAPED_HOSTS)
+    }
+
+    async fn extract(&self, client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let page = gfycat_page(url).ok_or(ExtractError::Unsupported)?;
+        let html = fetch_text(client, page.clone()).await?;
+        parse_gfycat_page(&page, &html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gfycat_pages_name_their_mp4() {
+        let base = Url::parse("https://gfycat.com/happypuppyexample")
+            .unwrap();
+        let html = include_str!("../../fixtures/gfycat/clip_page.html");
+        let media = parse_gfycat_page(&base, html).unwrap();
+        assert_eq!(media.kind, MediaKind::Gif);
+        assert!(media.looping);
+        assert_eq!(media.sources[0].url,
+                   "https://thumbs.gfycat.com/HappyPuppyExample-mobile.mp4");
+        assert_eq!(media.poster.as_deref(), Some(
+            "https://thumbs.gfycat.com/HappyPuppyExample-mobile.jpg"));
+    }
+
+    #[test]
+    fn removed_gfycat_pages_are_malformed() {
+        let base = Url::parse("https://gfycat.com/gone").unwrap();
+        let html = include_str!("../../fixtures/gfycat/removed_page.html");
+        assert!(matches!(parse_gfycat_page(&base, html),
+                         Err(ExtractError::Malformed(_))));
+    }
+
+    #[test]
+    fn gfycat_files_lead_to_their_page() {
+        let url = Url::parse(
+            "https://thumbs.gfycat.com/HappyPuppyExample-size_restricted.gif")
+            .unwrap();
+        assert_eq!(gfycat_page(&url).unwrap().as_str(),
+                   "https://gfycat.com/HappyPuppyExample");
+    }
+
+    #[test]
+    fn giphy_ids_are_found_in_pages_and_files() {
+        let id = |url| giphy_id(&Url::parse(url).unwrap());
+        let expected = Some("3o7TKSjRrfIPjeiVyM".to_string());
+        assert_eq!(id("https://giphy.com/gifs/cat-3o7TKSjRrfIPjeiVyM"),
+                   expected);
+        assert_eq!(id("https://media.giphy.com/media/3o7TKSjRrfIPjeiVyM\
+                       /giphy.gif"), expected);
+        assert_eq!(id("https://media.giphy.com/media/v1.Y2lk/\
+                       3o7TKSjRrfIPjeiVyM/giphy.gif"), expected);
+        assert_eq!(id("https://i.giphy.com/3o7TKSjRrfIPjeiVyM.webp"),
+                   expected);
+        assert_eq!(id("https://giphy.com/explore/cats/trending"), None);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
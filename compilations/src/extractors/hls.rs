@@ -0,0 +1,274 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            hls.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Parser for HLS master and media playlists.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::HashMap;
+
+use model::{MediaDescriptor, MediaKind, MediaSource};
+use reqwest::{Client, Url};
+use super::{fetch_text, ExtractError};
+
+pub const MIME_TYPE: &'static str = "application/vnd.apple.mpegurl";
+
+// The variant streams of a master playlist. Its audio renditions are left
+// to the HLS player, which finds them in the playlist itself.
+#[derive(Debug, Default)]
+pub struct MasterPlaylist {
+    pub variants: Vec<MediaSource>,
+}
+
+// The parts of a media playlist we care about.
+#[derive(Debug, Default)]
+pub struct MediaPlaylist {
+    pub segments: Vec<String>,
+
+    // Seconds
+    pub duration: f64,
+
+    // Whether the playlist is complete (i.e. not a live stream).
+    pub ended: bool,
+}
+
+// Parse an attribute list, e.g. 'BANDWIDTH=800000,CODECS="avc1,mp4a"'.
+fn parse_attributes(list: &str) -> HashMap<String, String> {
+    let mut attributes = HashMap::new();
+    let mut rest = list.trim();
+    while let Some((name, value)) = rest.split_once('=') {
+        let (value, remainder) = match value.strip_prefix('"') {
+            Some(quoted) => match quoted.split_once('"') {
+                Some((value, remainder)) => (value, remainder),
+                None => (quoted, ""),
+            },
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+        attributes.insert(name.trim().to_string(), value.to_string());
+        rest = remainder.trim_start_matches(',').trim_start();
+    }
+    attributes
+}
+
+fn check_header(playlist: &str) -> Result<(), ExtractError> {
+    match playlist.trim_start().starts_with("#EXTM3U") {
+        true => Ok(()),
+        false => Err(ExtractError::Malformed(
+            "playlist is missing #EXTM3U".to_string())),
+    }
+}
+
+fn resolve(base: &Url, uri: &str) -> Result<String, ExtractError> {
+    base.join(uri)
+        .map(|url| url.to_string())
+        .map_err(|e| ExtractError::Malformed(e.to_string()))
+}
+
+// Whether the playlist is a master playlist, rather than a media playlist.
+pub fn is_master(playlist: &str) -> bool {
+    playlist.lines().any(|line| line.starts_with("#EXT-X-STREAM-INF:"))
+}
+
+// Parse a master playlist. Variants are sorted best quality first.
+pub fn parse_master(base: &Url, playlist: &str) ->
+    Result<MasterPlaylist, ExtractError>
+{
+    check_header(playlist)?;
+    let mut master = MasterPlaylist::default();
+    let mut lines = playlist.lines().map(|line| line.trim());
+    while let Some(line) = lines.next() {
+        if let Some(list) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            let attributes = parse_attributes(list);
+            // The URI is on the next line that isn't a tag or comment.
+            let uri = lines.by_ref()
+                .find(|line| !line.is_empty() && !line.starts_with('#'))
+                .ok_or_else(|| ExtractError::Malformed(
+                    "EXT-X-STREAM-INF without a URI".to_string()))?;
+            let resolution = attributes.get("RESOLUTION")
+                .and_then(|resolution| resolution.split_once('x'));
+            master.variants.push(MediaSource {
+                url: resolve(base, uri)?,
+                mime_type: Some(MIME_TYPE.to_string()),
+                width: resolution.and_then(|(width, _)| width.parse().ok()),
+                height: resolution
+                    .and_then(|(_, height)| height.parse().ok()),
+                bandwidth: attributes.get("BANDWIDTH")
+                    .and_then(|bandwidth| bandwidth.parse().ok()),
+                codecs: attributes.get("CODECS").cloned(),
+            });
+        }
+    }
+
+    if master.variants.is_empty() {
+        return Err(ExtractError::Malformed(
+            "master playlist contains no variants".to_string()));
+    }
+    master.variants.sort_by_key(|variant| {
+        std::cmp::Reverse((variant.height, variant.bandwidth))
+    });
+    Ok(master)
+}
+
+pub fn parse_media(base: &Url, playlist: &str) ->
+    Result<MediaPlaylist, ExtractError>
+{
+    check_header(playlist)?;
+    let mut media = MediaPlaylist::default();
+    for line in playlist.lines().map(|line| line.trim()) {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            let duration = info.split(',').next().unwrap_or("");
+            media.duration += duration.trim().parse::<f64>()
+                .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+        } else if line == "#EXT-X-ENDLIST" {
+            media.ended = true;
+        } else if !line.is_empty() && !line.starts_with('#') {
+            media.segments.push(resolve(base, line)?);
+        }
+    }
+
+    if media.segments.is_empty() {
+        return Err(ExtractError::Malformed(
+            "media playlist contains no segments".to_string()));
+    }
+    Ok(media)
+}
+
+// Describe the stream behind an HLS playlist of either kind. For a master
+// playlist, the duration is taken from its best variant.
+pub async fn describe(client: &Client, url: &Url) ->
+    Result<MediaDescriptor, ExtractError>
+{
+    let playlist = fetch_text(client, url.clone()).await?;
+    if !is_master(&playlist) {
+        let media = parse_media(url, &playlist)?;
+        return Ok(MediaDescriptor {
+            kind: MediaKind::Video,
+            sources: vec![MediaSource {
+                mime_type: Some(MIME_TYPE.to_string()),
+                ..MediaSource::new(url.to_string())
+            }],
+            duration: Some(media.duration).filter(|_| media.ended),
+            ..Default::default()
+        });
+    }
+
+    let master = parse_master(url, &playlist)?;
+    let variant_url = Url::parse(&master.variants[0].url)
+        .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+    let variant = fetch_text(client, variant_url.clone()).await?;
+    let media = parse_media(&variant_url, &variant)?;
+    Ok(MediaDescriptor {
+        kind: MediaKind::Video,
+        sources: master.variants,
+        duration: Some(media.duration).filter(|_| media.ended),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASTER: &str = "#EXTM3U
+#EXT-X-VERSION:6
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"audio\",NAME=\"1\",\
+    URI=\"HLS_AUDIO.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=1200000,CODECS=\"avc1.4d401e,mp4a.40.2\",\
+    RESOLUTION=640x360,AUDIO=\"audio\"
+HLS_360.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=4800000,CODECS=\"avc1.4d401f,mp4a.40.2\",\
+    RESOLUTION=1280x720,AUDIO=\"audio\"
+
+# The URI is on the next line that isn't a comment
+HLS_720.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=450000,RESOLUTION=426x240
+/other/HLS_240.m3u8
+";
+
+    const MEDIA: &str = "#EXTM3U
+#EXT-X-VERSION:3
+#EXT-X-TARGETDURATION:4
+#EXTINF:4.000,
+HLS_720_0.ts
+#EXTINF:4.000,
+segments/HLS_720_1.ts
+#EXTINF:1.5,last one
+https://cdn.example.com/HLS_720_2.ts
+#EXT-X-ENDLIST
+";
+
+    fn base() -> Url {
+        Url::parse("https://v.redd.it/k2x9qz0abcd81/HLSPlaylist.m3u8")
+            .unwrap()
+    }
+
+    #[test]
+    fn quoted_attributes_may_contain_commas() {
+        let attributes = parse_attributes(
+            "BANDWIDTH=800000,CODECS=\"avc1.4d401e,mp4a.40.2\",\
+             RESOLUTION=640x360, NAME=\"a, b\"");
+        assert_eq!(attributes["BANDWIDTH"], "800000");
+        assert_eq!(attributes["CODECS"], "avc1.4d401e,mp4a.40.2");
+        assert_eq!(attributes["RESOLUTION"], "640x360");
+        assert_eq!(attributes["NAME"], "a, b");
+    }
+
+    #[test]
+    fn master_playlists_are_told_from_media_playlists() {
+        assert!(is_master(MASTER));
+        assert!(!is_master(MEDIA));
+    }
+
+    #[test]
+    fn variants_are_resolved_and_sorted_best_first() {
+        let master = parse_master(&base(), MASTER).unwrap();
+        let urls = master.variants.iter()
+            .map(|variant| variant.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(urls, [
+            "https://v.redd.it/k2x9qz0abcd81/HLS_720.m3u8",
+            "https://v.redd.it/k2x9qz0abcd81/HLS_360.m3u8",
+            "https://v.redd.it/other/HLS_240.m3u8",
+        ]);
+        assert_eq!(master.variants[0].width, Some(1280));
+        assert_eq!(master.variants[0].height, Some(720));
+        assert_eq!(master.variants[0].bandwidth, Some(4800000));
+        assert_eq!(master.variants[0].codecs.as_deref(),
+                   Some("avc1.4d401f,mp4a.40.2"));
+        assert_eq!(master.variants[2].codecs, None);
+    }
+
+    #[test]
+    fn media_playlists_add_up_their_segments() {
+        let media = parse_media(&base(), MEDIA).unwrap();
+        assert_eq!(media.segments, [
+            "https://v.redd.it/k2x9qz0abcd81/HLS_720_0.ts",
+            "https://v.redd.it/k2x9qz0abcd81/segments/HLS_720_1.ts",
+            "https://cdn.example.com/HLS_720_2.ts",
+        ]);
+        assert_eq!(media.duration, 9.5);
+        assert!(media.ended);
+
+        let live = MEDIA.replace("#EXT-X-ENDLIST\n", "");
+        assert!(!parse_media(&base(), &live).unwrap().ended);
+    }
+
+    #[test]
+    fn broken_playlists_are_malformed() {
+        assert!(matches!(parse_media(&base(), "HLS_720_0.ts\n"),
+                         Err(ExtractError::Malformed(_))));
+        assert!(matches!(parse_media(&base(), "#EXTM3U\n#EXT-X-ENDLIST\n"),
+                         Err(ExtractError::Malformed(_))));
+        assert!(matches!(
+            parse_master(&base(), "#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1\n"),
+            Err(ExtractError::Malformed(_))));
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,298 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            imgur.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Extractor for Imgur images, .gifv clips and albums.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaKind, MediaSource};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use super::page::Page;
+use super::{fetch_text, ExtractError, Extractor};
+
+const HOSTS: &[&'static str] = &[
+    "imgur.com", "www.imgur.com", "m.imgur.com", "i.imgur.com",
+];
+
+// Imgur transcodes animated images to MP4, which is what .gifv pages play.
+const ANIMATED_EXTENSIONS: &[&'static str] = &["gifv", "gif", "mp4", "webm"];
+
+pub struct ImgurExtractor;
+
+// The kinds of Imgur link we know how to follow.
+enum Link {
+    // A file on i.imgur.com, e.g. /abc123.gifv
+    File { id: String, extension: String },
+
+    // The page of a single image, e.g. imgur.com/abc123
+    Page(String),
+
+    // An album or gallery post, e.g. imgur.com/a/abc123, or
+    // imgur.com/gallery/some-title-abc123
+    Album(String),
+}
+
+// Response of the album endpoint. The data is an empty list, rather than an
+// object, when the album doesn't exist.
+#[derive(Deserialize)]
+struct AlbumResponse {
+    data: AlbumData,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AlbumData {
+    Album { images: Vec<AlbumImage> },
+    Missing(serde::de::IgnoredAny),
+}
+
+#[derive(Deserialize)]
+struct AlbumImage {
+    hash: String,
+    ext: String,
+    #[serde(default)]
+    animated: bool,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+fn classify(url: &Url) -> Option<Link> {
+    let segments = url.path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        ["a", slug] | ["gallery", slug] => slug.rsplit('-').next()
+            .map(|id| Link::Album(id.to_string())),
+        [file] => match file.split_once('.') {
+            Some((id, extension)) => Some(Link::File {
+                id: id.to_string(),
+                extension: extension.to_lowercase(),
+            }),
+            None => Some(Link::Page(file.to_string())),
+        },
+        _ => None,
+    }
+}
+
+// Describe a file on i.imgur.com. Animated files are described by their MP4
+// version, since that's all the .gifv page is.
+fn describe_file(id: &str, extension: &str) -> MediaDescriptor {
+    if ANIMATED_EXTENSIONS.contains(&extension) {
+        return MediaDescriptor {
+            kind: MediaKind::Gif,
+            looping: true,
+            sources: vec![MediaSource {
+                mime_type: Some("video/mp4".to_string()),
+                ..MediaSource::new(format!("https://i.imgur.com/{}.mp4", id))
+            }],
+            poster: Some(format!("https://i.imgur.com/{}h.jpg", id)),
+            ..Default::default()
+        };
+    }
+
+    let url = format!("https://i.imgur.com/{}.{}", id, extension);
+    MediaDescriptor {
+        kind: MediaKind::Image,
+        sources: vec![MediaSource {
+            mime_type: mime_guess::from_ext(extension).first()
+                .map(|mime_type| mime_type.to_string()),
+            ..MediaSource::new(url)
+        }],
+        ..Default::default()
+    }
+}
+
+// Parse the page of a single image, which names the file in its OpenGraph
+// tags: og:video for animated images, og:image otherwise.
+pub fn parse_page(base: &Url, html: &str) ->
+    Result<MediaDescriptor, ExtractError>
+{
+    let page = Page::parse(html);
+    let (kind, source) = match page.og_video(base) {
+        Some(video) => (MediaKind::Gif, video),
+        None => (MediaKind::Image, page.og_image(base)
+                 .ok_or_else(|| ExtractError::Malformed(
+                     "page has no og:video or og:image".to_string()))?),
+    };
+    Ok(MediaDescriptor {
+        kind,
+        sources: vec![source],
+        looping: kind == MediaKind::Gif,
+        ..Default::default()
+    })
+}
+
+// Parse the response of the album endpoint into a gallery. Albums with only
+// one image are described as that image.
+pub fn parse_album(json: &str) -> Result<MediaDescriptor, ExtractError> {
+    let response: AlbumResponse = serde_json::from_str(json)
+        .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+    let images = match response.data {
+        AlbumData::Album { images } => images,
+        AlbumData::Missing(_) => return Err(ExtractError::NotFound),
+    };
+
+    let mut items = images.into_iter().map(|image| {
+        let extension = image.ext.trim_start_matches('.').to_lowercase();
+        let mut item = match image.animated {
+            true => describe_file(&image.hash, "gifv"),
+            false => describe_file(&image.hash, &extension),
+        };
+        for source in item.sources.iter_mut() {
+            source.width = image.width;
+            source.height = image.height;
+        }
+        item
+    }).collect::<Vec<_>>();
+
+    match items.len() {
+        0 => Err(ExtractError::NotFound),
+        1 => Ok(items.remove(0)),
+        _ => Ok(MediaDescriptor {
+            kind: MediaKind::Gallery,
+            items,
+            ..Default::default()
+        }),
+    }
+}
+
+#[async_trait]
+impl Extractor for ImgurExtractor {
+    fn name(&self) -> &'static str {
+        "imgur"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        url.host_str().map(|host| HOSTS.contains(&host)).unwrap_or(false)
+    }
+
+    async fn extract(&self, client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let parse_url = |url: String| Url::parse(&url)
+            .map_err(|e| ExtractError::Malformed(e.to_string()));
+        match classify(url).ok_or(ExtractError::Unsupported)? {
+            Link::File { id, extension } =>
+                Ok(describe_file(&id, &extension)),
+
+            Link::Page(id) => {
+                let page = parse_url(format!("https://imgur.com/{}", id))?;
+                let html = fetch_text(client, page.clone()).await?;
+                parse_page(&page, &html)
+            },
+
+            Link::Album(id) => {
+                let endpoint = parse_url(format!(
+                    "https://imgur.com/ajaxalbums/getimages/{}/hit.json", id
+                ))?;
+                parse_album(&fetch_text(client, endpoint).await?)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str) -> Url {
+        Url::parse(&format!("https://imgur.com/{}", id)).unwrap()
+    }
+
+    #[test]
+    fn image_pages_name_their_image() {
+        let html = include_str!("../../fixtures/imgur/image_page.html");
+        let media = parse_page(&page("AbC1234"), html).unwrap();
+        assert_eq!(media.kind, MediaKind::Image);
+        assert!(!media.looping);
+        assert_eq!(media.sources[0].url,
+                   "https://i.imgur.com/AbC1234.jpeg?fb");
+        assert_eq!(media.sources[0].width, Some(1200));
+    }
+
+    #[test]
+    fn gifv_pages_name_their_mp4() {
+        let html = include_str!("../../fixtures/imgur/gifv_page.html");
+        let media = parse_page(&page("XyZ9876"), html).unwrap();
+        assert_eq!(media.kind, MediaKind::Gif);
+        assert!(media.looping);
+        assert_eq!(media.sources[0].url, "https://i.imgur.com/XyZ9876.mp4");
+        assert_eq!(media.sources[0].mime_type.as_deref(), Some("video/mp4"));
+        assert_eq!(media.sources[0].height, Some(360));
+    }
+
+    #[test]
+    fn removed_pages_are_malformed() {
+        let html = include_str!("../../fixtures/imgur/removed_page.html");
+        assert!(matches!(parse_page(&page("Gone123"), html),
+                         Err(ExtractError::Malformed(_))));
+    }
+
+    #[test]
+    fn albums_are_galleries() {
+        let json = include_str!("../../fixtures/imgur/album.json");
+        let media = parse_album(json).unwrap();
+        assert_eq!(media.kind, MediaKind::Gallery);
+        assert_eq!(media.items.len(), 3);
+
+        let first = &media.items[0];
+        assert_eq!(first.kind, MediaKind::Image);
+        assert_eq!(first.sources[0].url, "https://i.imgur.com/Qw3rTy1.jpg");
+        assert_eq!(first.sources[0].width, Some(1080));
+
+        // Animated images are played from their MP4 version.
+        let second = &media.items[1];
+        assert_eq!(second.kind, MediaKind::Gif);
+        assert_eq!(second.sources[0].url, "https://i.imgur.com/As4dFg2.mp4");
+        assert_eq!(second.poster.as_deref(),
+                   Some("https://i.imgur.com/As4dFg2h.jpg"));
+
+        // Extensions are normalised.
+        assert_eq!(media.items[2].sources[0].url,
+                   "https://i.imgur.com/Zx5cVb3.png");
+    }
+
+    #[test]
+    fn single_image_albums_are_that_image() {
+        let json = include_str!(
+            "../../fixtures/imgur/single_image_album.json");
+        let media = parse_album(json).unwrap();
+        assert_eq!(media.kind, MediaKind::Image);
+        assert!(media.items.is_empty());
+        assert_eq!(media.sources[0].url, "https://i.imgur.com/Qw3rTy1.jpg");
+    }
+
+    #[test]
+    fn missing_albums_are_not_found() {
+        let json = include_str!("../../fixtures/imgur/missing_album.json");
+        assert!(matches!(parse_album(json), Err(ExtractError::NotFound)));
+    }
+
+    #[test]
+    fn truncated_albums_are_malformed() {
+        let json = include_str!("../../fixtures/imgur/truncated_album.json");
+        assert!(matches!(parse_album(json), Err(ExtractError::Malformed(_))));
+    }
+
+    #[test]
+    fn links_are_classified() {
+        let classify = |url| classify(&Url::parse(url).unwrap());
+        assert!(matches!(classify("https://i.imgur.com/AbC1234.GIFV"),
+                         Some(Link::File { id, extension })
+                         if id == "AbC1234" && extension == "gifv"));
+        assert!(matches!(classify("https://imgur.com/AbC1234"),
+                         Some(Link::Page(id)) if id == "AbC1234"));
+        assert!(matches!(classify("https://imgur.com/gallery/a-title-Xy12"),
+                         Some(Link::Album(id)) if id == "Xy12"));
+        assert!(classify("https://imgur.com/user/someone/posts").is_none());
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
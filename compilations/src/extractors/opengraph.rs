@@ -0,0 +1,349 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            opengraph.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Fallback extractor for pages that describe their media
+//                  with OpenGraph, Twitter cards, <video> tags or JSON-LD.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaKind, MediaSource};
+use reqwest::{Client, Url};
+use serde_json::Value;
+use crate::public_address;
+use super::page::Page;
+use super::reddit_video::parse_duration;
+use super::{fetch_text, ExtractError, Extractor};
+
+// Used for hosts that no other extractor knows about. Most sites that embed
+// video describe it in their markup for the benefit of link previews, which
+// is usually enough to find a file the browser can play.
+pub struct OpenGraphExtractor;
+
+// Everything a page told us about its media.
+#[derive(Default)]
+struct Candidates {
+    videos: Vec<MediaSource>,
+
+    // Player pages (e.g. og:video with type text/html), for when there's no
+    // video file to be had.
+    players: Vec<MediaSource>,
+    poster: Option<String>,
+    duration: Option<f64>,
+}
+
+fn parse_number<T: std::str::FromStr>(value: Option<String>) -> Option<T> {
+    value.and_then(|value| value.trim().parse().ok())
+}
+
+// The MIME type of the source, guessing from its extension if the page
+// didn't say.
+fn mime_type(source: &MediaSource) -> Option<String> {
+    source.mime_type.clone().or_else(|| {
+        let url = Url::parse(&source.url).ok()?;
+        mime_guess::from_path(url.path()).first()
+            .map(|mime_type| mime_type.to_string())
+    })
+}
+
+impl Candidates {
+    // Sort the source into videos and players. Anything else (images,
+    // Flash players, or files of unknown type) is of no use to us.
+    fn add(&mut self, mut source: MediaSource) {
+        source.mime_type = mime_type(&source);
+        let list = match source.mime_type.as_deref() {
+            Some(super::hls::MIME_TYPE) => &mut self.videos,
+            Some(mime_type) if mime_type.starts_with("video/") =>
+                &mut self.videos,
+            Some("text/html") => &mut self.players,
+            _ => return,
+        };
+        if !list.iter().any(|existing| existing.url == source.url) {
+            list.push(source);
+        }
+    }
+
+    fn from_meta(&mut self, base: &Url, page: &Page) {
+        if let Some(video) = page.og_video(base) {
+            self.add(video);
+        }
+
+        if let Some(stream) = page.meta("twitter:player:stream")
+            .and_then(|stream| base.join(&stream).ok())
+        {
+            self.add(MediaSource {
+                mime_type: page.meta("twitter:player:stream:content_type")
+                    .map(|content_type| {
+                        // e.g. 'video/mp4; codecs="avc1.42E01E1"'
+                        content_type.split(';').next().unwrap_or("")
+                            .trim().to_string()
+                    }),
+                width: parse_number(page.meta("twitter:player:width")),
+                height: parse_number(page.meta("twitter:player:height")),
+                ..MediaSource::new(stream.to_string())
+            });
+        }
+
+        if let Some(player) = page.meta("twitter:player")
+            .and_then(|player| base.join(&player).ok())
+        {
+            self.add(MediaSource {
+                mime_type: Some("text/html".to_string()),
+                ..MediaSource::new(player.to_string())
+            });
+        }
+
+        self.poster = self.poster.take()
+            .or_else(|| page.og_image(base).map(|image| image.url))
+            .or_else(|| page.meta("twitter:image")
+                     .and_then(|image| base.join(&image).ok())
+                     .map(|image| image.to_string()));
+        self.duration = self.duration
+            .or_else(|| parse_number(page.meta("og:video:duration")))
+            .or_else(|| parse_number(page.meta("video:duration")));
+    }
+
+    fn from_video_tags(&mut self, base: &Url, page: &Page) {
+        for video in page.select("video") {
+            let attributes = video.attributes.borrow();
+            let width = parse_number(attributes.get("width")
+                                     .map(String::from));
+            let height = parse_number(attributes.get("height")
+                                      .map(String::from));
+            if self.poster.is_none() {
+                self.poster = attributes.get("poster")
+                    .and_then(|poster| base.join(poster).ok())
+                    .map(|poster| poster.to_string());
+            }
+
+            let mut sources = Vec::new();
+            if let Some(src) = attributes.get("src") {
+                sources.push((src.to_string(), None));
+            }
+            for source in video.as_node().select("source").into_iter()
+                .flatten()
+            {
+                let attributes = source.attributes.borrow();
+                if let Some(src) = attributes.get("src") {
+                    sources.push((src.to_string(),
+                                  attributes.get("type").map(String::from)));
+                }
+            }
+
+            for (src, mime_type) in sources {
+                if let Ok(url) = base.join(&src) {
+                    self.add(MediaSource {
+                        mime_type, width, height,
+                        ..MediaSource::new(url.to_string())
+                    });
+                }
+            }
+        }
+    }
+
+    fn from_video_object(&mut self, base: &Url, object: &Value) {
+        let string = |key: &str| object.get(key).and_then(|value| {
+            match value {
+                Value::String(value) => Some(value.clone()),
+                Value::Number(value) => Some(value.to_string()),
+                // e.g. "thumbnailUrl": ["https://..."]
+                Value::Array(values) => values.first()
+                    .and_then(|value| value.as_str())
+                    .map(String::from),
+                _ => None,
+            }
+        });
+
+        if let Some(url) = string("contentUrl")
+            .and_then(|url| base.join(&url).ok())
+        {
+            self.add(MediaSource {
+                mime_type: string("encodingFormat")
+                    .filter(|format| format.contains('/')),
+                width: parse_number(string("width")),
+                height: parse_number(string("height")),
+                bandwidth: parse_number(string("bitrate")),
+                ..MediaSource::new(url.to_string())
+            });
+        }
+        if let Some(url) = string("embedUrl")
+            .and_then(|url| base.join(&url).ok())
+        {
+            self.add(MediaSource {
+                mime_type: Some("text/html".to_string()),
+                ..MediaSource::new(url.to_string())
+            });
+        }
+
+        self.poster = self.poster.take().or_else(|| {
+            string("thumbnailUrl")
+                .and_then(|image| base.join(&image).ok())
+                .map(|image| image.to_string())
+        });
+        self.duration = self.duration.or_else(|| {
+            string("duration").as_deref().and_then(parse_duration)
+        });
+    }
+
+    // Walk a JSON-LD document for VideoObjects, which may be at the top
+    // level, in an array, in an @graph, or nested in another object (e.g.
+    // the "video" of an Article).
+    fn from_json_ld(&mut self, base: &Url, value: &Value) {
+        match value {
+            Value::Array(values) => for value in values {
+                self.from_json_ld(base, value);
+            },
+            Value::Object(object) => {
+                let is_video = match object.get("@type") {
+                    Some(Value::String(kind)) => kind == "VideoObject",
+                    Some(Value::Array(kinds)) => kinds.iter()
+                        .any(|kind| kind.as_str() == Some("VideoObject")),
+                    _ => false,
+                };
+                if is_video {
+                    self.from_video_object(base, value);
+                }
+                for value in object.values() {
+                    self.from_json_ld(base, value);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn from_scripts(&mut self, base: &Url, page: &Page) {
+        for script in page.select(r#"script[type="application/ld+json"]"#) {
+            let text = script.text_contents();
+            if let Ok(value) = serde_json::from_str::<Value>(&text) {
+                self.from_json_ld(base, &value);
+            }
+        }
+    }
+}
+
+// Parse the page into a descriptor of the best video it describes, with the
+// others as fallbacks. Pages that only offer an embeddable player are
+// described as an embed.
+pub fn parse_page(base: &Url, html: &str) ->
+    Result<MediaDescriptor, ExtractError>
+{
+    let page = Page::parse(html);
+    let mut candidates = Candidates::default();
+    candidates.from_meta(base, &page);
+    candidates.from_video_tags(base, &page);
+    candidates.from_scripts(base, &page);
+
+    let Candidates { mut videos, players, poster, duration } = candidates;
+    let kind = match videos.is_empty() {
+        false => MediaKind::Video,
+        true => MediaKind::Embed,
+    };
+    let sources = match kind {
+        MediaKind::Video => {
+            // Stable, so sources of the same size stay in page order.
+            videos.sort_by_key(|source| {
+                std::cmp::Reverse((source.height, source.bandwidth))
+            });
+            videos
+        },
+        _ => players.into_iter().take(1).collect(),
+    };
+
+    match sources.is_empty() {
+        true => Err(ExtractError::Unsupported),
+        false => Ok(MediaDescriptor {
+            kind, sources, poster, duration,
+            ..Default::default()
+        }),
+    }
+}
+
+#[async_trait]
+impl Extractor for OpenGraphExtractor {
+    fn name(&self) -> &'static str {
+        "opengraph"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        public_address::is_public_url(url)
+    }
+
+    async fn extract(&self, client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let html = fetch_text(client, url.clone()).await?;
+        parse_page(url, &html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        Url::parse("https://example.com/videos/123").unwrap()
+    }
+
+    #[test]
+    fn og_video_is_the_video_and_og_image_its_poster() {
+        let html = include_str!("../../fixtures/opengraph/video_page.html");
+        let media = parse_page(&base(), html).unwrap();
+        assert_eq!(media.kind, MediaKind::Video);
+        let sources = media.sources.iter()
+            .map(|source| (source.url.as_str(), source.height))
+            .collect::<Vec<_>>();
+        assert_eq!(sources, [
+            ("https://example.com/media/123/clip_720.mp4", Some(720)),
+            ("https://example.com/videos/clip_480.webm", Some(480)),
+        ]);
+        assert_eq!(media.sources[1].mime_type.as_deref(), Some("video/webm"));
+        assert_eq!(media.poster.as_deref(),
+                   Some("https://example.com/media/123/poster.jpg"));
+        assert_eq!(media.duration, Some(42.0));
+    }
+
+    #[test]
+    fn player_pages_are_embeds() {
+        let html = include_str!("../../fixtures/opengraph/player_page.html");
+        let media = parse_page(&base(), html).unwrap();
+        assert_eq!(media.kind, MediaKind::Embed);
+        assert_eq!(media.sources.len(), 1);
+        assert_eq!(media.sources[0].url, "https://example.com/embed/456");
+        assert_eq!(media.poster.as_deref(),
+                   Some("https://cdn.example.com/456/poster.jpg"));
+        assert_eq!(media.duration, Some(62.5));
+    }
+
+    #[test]
+    fn json_ld_urls_are_relative_to_the_page() {
+        let html = include_str!("../../fixtures/opengraph/stream_page.html");
+        let media = parse_page(&base(), html).unwrap();
+        assert_eq!(media.kind, MediaKind::Video);
+        assert_eq!(media.sources[0].url,
+                   "https://example.com/videos/hls/789/master.m3u8");
+        assert_eq!(media.sources[0].mime_type.as_deref(),
+                   Some(crate::extractors::hls::MIME_TYPE));
+        assert_eq!(media.sources[0].height, Some(1080));
+        assert_eq!(media.poster.as_deref(),
+                   Some("https://example.com/videos/hls/789/thumb.jpg"));
+        assert_eq!(media.duration, Some(3720.0));
+    }
+
+    #[test]
+    fn pages_without_video_are_unsupported() {
+        for html in [
+            include_str!("../../fixtures/opengraph/image_page.html"),
+            include_str!("../../fixtures/opengraph/plain_page.html"),
+        ] {
+            assert!(matches!(parse_page(&base(), html),
+                             Err(ExtractError::Unsupported)));
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,132 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            page.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Helpers for scraping the HTML pages of media hosts.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use kuchiki::traits::TendrilSink;
+use kuchiki::{ElementData, NodeDataRef, NodeRef};
+use model::MediaSource;
+use reqwest::Url;
+
+// A parsed HTML page. Pages aren't Send, so they should be parsed and
+// scraped between awaits, never held across one.
+pub struct Page {
+    document: NodeRef,
+}
+
+impl Page {
+    pub fn parse(html: &str) -> Self {
+        Self { document: kuchiki::parse_html().one(html) }
+    }
+
+    // Every element matching the CSS selector, in document order.
+    pub fn select(&self, selector: &str) -> Vec<NodeDataRef<ElementData>> {
+        match self.document.select(selector) {
+            Ok(elements) => elements.collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    // The content of the first <meta> tag whose property (OpenGraph) or name
+    // (Twitter cards) is the key.
+    pub fn meta(&self, key: &str) -> Option<String> {
+        self.document.select("meta").ok()?.find_map(|meta| {
+            let attributes = meta.attributes.borrow();
+            let matches = attributes.get("property") == Some(key)
+                || attributes.get("name") == Some(key);
+            attributes.get("content")
+                .filter(|content| matches && !content.trim().is_empty())
+                .map(|content| content.trim().to_string())
+        })
+    }
+
+    // The first of the keys that has a <meta> tag.
+    pub fn meta_any(&self, keys: &[&str]) -> Option<String> {
+        keys.iter().find_map(|key| self.meta(key))
+    }
+
+    // The video described by the page's OpenGraph tags, if any. Relative
+    // URLs are resolved against the page's URL.
+    pub fn og_video(&self, base: &Url) -> Option<MediaSource> {
+        let location = self.meta_any(&[
+            "og:video:secure_url", "og:video:url", "og:video",
+        ])?;
+        Some(MediaSource {
+            url: base.join(&location).ok()?.to_string(),
+            mime_type: self.meta("og:video:type"),
+            width: self.meta("og:video:width")
+                .and_then(|width| width.parse().ok()),
+            height: self.meta("og:video:height")
+                .and_then(|height| height.parse().ok()),
+            ..Default::default()
+        })
+    }
+
+    // The image described by the page's OpenGraph tags, if any.
+    pub fn og_image(&self, base: &Url) -> Option<MediaSource> {
+        let location = self.meta_any(&[
+            "og:image:secure_url", "og:image:url", "og:image",
+        ])?;
+        Some(MediaSource {
+            url: base.join(&location).ok()?.to_string(),
+            mime_type: self.meta("og:image:type"),
+            width: self.meta("og:image:width")
+                .and_then(|width| width.parse().ok()),
+            height: self.meta("og:image:height")
+                .and_then(|height| height.parse().ok()),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secure_urls_are_preferred() {
+        let html = include_str!("../../fixtures/imgur/gifv_page.html");
+        let base = Url::parse("https://imgur.com/XyZ9876").unwrap();
+        let video = Page::parse(html).og_video(&base).unwrap();
+        assert_eq!(video.url, "https://i.imgur.com/XyZ9876.mp4");
+        assert_eq!(video.width, Some(640));
+    }
+
+    #[test]
+    fn twitter_cards_are_found_by_name() {
+        let html = include_str!("../../fixtures/imgur/image_page.html");
+        let page = Page::parse(html);
+        assert_eq!(page.meta("twitter:card").as_deref(),
+                   Some("summary_large_image"));
+        assert_eq!(page.meta_any(&["og:video", "twitter:image"]).as_deref(),
+                   Some("https://i.imgur.com/AbC1234h.jpeg"));
+    }
+
+    #[test]
+    fn blank_tags_are_ignored() {
+        let html = include_str!("../../fixtures/imgur/removed_page.html");
+        let base = Url::parse("https://imgur.com/Gone123").unwrap();
+        let page = Page::parse(html);
+        assert_eq!(page.meta("og:title"), None);
+        assert!(page.og_image(&base).is_none());
+    }
+
+    #[test]
+    fn relative_urls_are_resolved() {
+        let html = r#"<html><head>
+            <meta property="og:image" content="/thumbs/clip.jpg">
+            </head></html>"#;
+        let base = Url::parse("https://example.com/watch/clip").unwrap();
+        let image = Page::parse(html).og_image(&base).unwrap();
+        assert_eq!(image.url, "https://example.com/thumbs/clip.jpg");
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
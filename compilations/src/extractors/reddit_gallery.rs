@@ -0,0 +1,290 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            reddit_gallery.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Extractor for Reddit-hosted image galleries.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaKind, MediaSource, MAX_CROSSPOST_DEPTH};
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use super::{fetch_text, ExtractError, Extractor};
+
+const HOSTS: &[&'static str] = &[
+    "reddit.com", "www.reddit.com", "old.reddit.com", "new.reddit.com",
+];
+
+// Gallery posts link to reddit.com/gallery/<id>. The images themselves are
+// listed in the post's gallery_data, and described in its media_metadata.
+pub struct RedditGalleryExtractor;
+
+#[derive(Deserialize)]
+struct Listing {
+    data: ListingData,
+}
+
+#[derive(Deserialize)]
+struct ListingData {
+    children: Vec<Child>,
+}
+
+#[derive(Deserialize)]
+struct Child {
+    data: PostData,
+}
+
+#[derive(Deserialize)]
+struct PostData {
+    gallery_data: Option<GalleryData>,
+    media_metadata: Option<HashMap<String, MediaMetadata>>,
+    #[serde(default)]
+    over_18: bool,
+    #[serde(default)]
+    spoiler: bool,
+
+    // Crossposts carry the gallery of the post they were crossposted from,
+    // rather than their own.
+    #[serde(default)]
+    crosspost_parent_list: Vec<PostData>,
+}
+
+#[derive(Deserialize)]
+struct GalleryData {
+    items: Vec<GalleryItem>,
+}
+
+#[derive(Deserialize)]
+struct GalleryItem {
+    media_id: String,
+}
+
+#[derive(Deserialize)]
+struct MediaMetadata {
+    // "valid", or e.g. "failed" for images that never finished processing
+    status: String,
+
+    // "Image" or "AnimatedImage"
+    e: Option<String>,
+
+    // MIME type, e.g. "image/jpg"
+    m: Option<String>,
+
+    // The full-size image
+    s: Option<Resolution>,
+
+    // Previews, smallest first
+    #[serde(default)]
+    p: Vec<Resolution>,
+}
+
+#[derive(Deserialize)]
+struct Resolution {
+    u: Option<String>,
+    gif: Option<String>,
+    mp4: Option<String>,
+    x: Option<u32>,
+    y: Option<u32>,
+}
+
+// The ID of the gallery post, from reddit.com/gallery/<id>.
+fn gallery_id(url: &Url) -> Option<String> {
+    let segments = url.path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        ["gallery", id] => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+fn source(url: String, mime_type: &str, resolution: &Resolution) ->
+    MediaSource
+{
+    MediaSource {
+        mime_type: Some(mime_type.to_string()),
+        width: resolution.x,
+        height: resolution.y,
+        ..MediaSource::new(url)
+    }
+}
+
+// Describe one image of the gallery, with the full-size version first and
+// the previews as fallbacks.
+fn describe_item(metadata: &MediaMetadata) -> Option<MediaDescriptor> {
+    if metadata.status != "valid" {
+        return None;
+    }
+    let full = metadata.s.as_ref()?;
+
+    if metadata.e.as_deref() == Some("AnimatedImage") {
+        let mut sources = Vec::new();
+        if let Some(mp4) = &full.mp4 {
+            sources.push(source(mp4.clone(), "video/mp4", full));
+        }
+        if let Some(gif) = &full.gif {
+            sources.push(source(gif.clone(), "image/gif", full));
+        }
+        return match sources.is_empty() {
+            true => None,
+            false => Some(MediaDescriptor {
+                kind: MediaKind::Gif,
+                looping: true,
+                sources,
+                ..Default::default()
+            }),
+        };
+    }
+
+    // Reddit calls JPEGs "image/jpg".
+    let mime_type = match metadata.m.as_deref() {
+        Some("image/jpg") | None => "image/jpeg",
+        Some(mime_type) => mime_type,
+    };
+    let sources = std::iter::once(full)
+        .chain(metadata.p.iter().rev())
+        .filter_map(|resolution| {
+            resolution.u.clone()
+                .map(|url| source(url, mime_type, resolution))
+        })
+        .collect::<Vec<_>>();
+    match sources.is_empty() {
+        true => None,
+        false => Some(MediaDescriptor {
+            kind: MediaKind::Image,
+            sources,
+            ..Default::default()
+        }),
+    }
+}
+
+// Parse the post's JSON (/comments/<id>.json?raw_json=1) into a gallery, in
+// the order the poster arranged it.
+pub fn parse_post(json: &str) -> Result<MediaDescriptor, ExtractError> {
+    let listings: Vec<Listing> = serde_json::from_str(json)
+        .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+    let mut post = listings.into_iter().next()
+        .and_then(|listing| listing.data.children.into_iter().next())
+        .ok_or(ExtractError::NotFound)?
+        .data;
+
+    // A crosspost may be marked NSFW even if the original isn't, or the
+    // other way around.
+    let (nsfw, spoiler) = (post.over_18, post.spoiler);
+    for _ in 0..MAX_CROSSPOST_DEPTH {
+        if post.gallery_data.is_some() || post.crosspost_parent_list.is_empty()
+        {
+            break;
+        }
+        post = post.crosspost_parent_list.swap_remove(0);
+    }
+
+    // Removed galleries lose their gallery_data.
+    let (gallery, metadata) = match (post.gallery_data, post.media_metadata) {
+        (Some(gallery), Some(metadata)) => (gallery, metadata),
+        _ => return Err(ExtractError::NotFound),
+    };
+    let items = gallery.items.iter()
+        .filter_map(|item| metadata.get(&item.media_id))
+        .filter_map(describe_item)
+        .collect::<Vec<_>>();
+    if items.is_empty() {
+        return Err(ExtractError::NotFound);
+    }
+
+    Ok(MediaDescriptor {
+        kind: MediaKind::Gallery,
+        poster: items[0].primary().map(|source| source.url.clone()),
+        items,
+        nsfw: nsfw || post.over_18,
+        spoiler: spoiler || post.spoiler,
+        ..Default::default()
+    })
+}
+
+#[async_trait]
+impl Extractor for RedditGalleryExtractor {
+    fn name(&self) -> &'static str {
+        "reddit gallery"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        url.host_str().map(|host| HOSTS.contains(&host)).unwrap_or(false)
+    }
+
+    async fn extract(&self, client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let id = gallery_id(url).ok_or(ExtractError::Unsupported)?;
+
+        // Without raw_json, the URLs in the response are HTML-escaped.
+        let post = Url::parse(&format!(
+            "https://www.reddit.com/comments/{}.json?raw_json=1", id))
+            .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+        parse_post(&fetch_text(client, post).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossposts_carry_the_original_gallery() {
+        let json = include_str!(
+            "../../fixtures/reddit/gallery_crosspost.json");
+        let media = parse_post(json).unwrap();
+        assert_eq!(media.kind, MediaKind::Gallery);
+
+        // The item that failed to process is left out.
+        assert_eq!(media.items.len(), 2);
+        assert_eq!(media.poster.as_deref(),
+                   Some("https://i.redd.it/a1b2c3.jpg"));
+
+        let image = &media.items[0];
+        assert_eq!(image.kind, MediaKind::Image);
+        assert_eq!(image.sources.len(), 3);
+        assert_eq!(image.sources[0].mime_type.as_deref(), Some("image/jpeg"));
+        assert_eq!(image.sources[1].width, Some(640));
+
+        let animation = &media.items[1];
+        assert_eq!(animation.kind, MediaKind::Gif);
+        assert!(animation.looping);
+        assert_eq!(animation.sources[0].mime_type.as_deref(),
+                   Some("video/mp4"));
+    }
+
+    #[test]
+    fn flags_of_the_crosspost_and_the_original_are_combined() {
+        let json = include_str!(
+            "../../fixtures/reddit/gallery_crosspost.json");
+        let media = parse_post(json).unwrap();
+        assert!(!media.nsfw);
+        assert!(media.spoiler);
+    }
+
+    #[test]
+    fn posts_without_galleries_are_not_found() {
+        for json in [
+            include_str!("../../fixtures/reddit/crosspost_of_crosspost.json"),
+            include_str!("../../fixtures/reddit/self_post_crosspost.json"),
+        ] {
+            assert!(matches!(parse_post(json), Err(ExtractError::NotFound)));
+        }
+    }
+
+    #[test]
+    fn malformed_posts_are_malformed() {
+        assert!(matches!(parse_post("[{\"kind\": \"Listing\""),
+                         Err(ExtractError::Malformed(_))));
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
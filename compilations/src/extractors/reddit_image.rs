@@ -0,0 +1,167 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            reddit_image.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Extractor for images and animated GIFs hosted by Reddit.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaKind, MediaSource};
+use reqwest::{Client, Url};
+use super::{direct, ExtractError, Extractor};
+
+const HOSTS: &[&'static str] = &[
+    "i.redd.it", "preview.redd.it", "external-preview.redd.it",
+];
+
+// Reddit transcodes animated images to MP4, which it serves from
+// preview.redd.it as the mp4 "variant" of the post's preview, e.g.
+// preview.redd.it/abc123.gif?format=mp4&s=<signature>. The signature covers
+// the query, so the URL of the variant can't be made up from the GIF's: it
+// has to come from the post. The original GIF is always on i.redd.it.
+pub struct RedditImageExtractor;
+
+// Reddit's listings HTML-escape the URLs of previews unless they're asked
+// not to, and the URL is of no use with "&amp;" in its query.
+fn unescape(url: &Url) -> Url {
+    let mut url = url.clone();
+    if let Some(query) = url.query().filter(|query| query.contains("&amp;"))
+    {
+        let query = query.replace("&amp;", "&");
+        url.set_query(Some(&query));
+    }
+    url
+}
+
+fn is_mp4_variant(url: &Url) -> bool {
+    url.query_pairs()
+        .any(|(key, value)| key == "format" && value == "mp4")
+}
+
+fn file_name(url: &Url) -> Option<&str> {
+    url.path_segments()?.filter(|segment| !segment.is_empty()).last()
+}
+
+fn is_gif(name: &str) -> bool {
+    name.to_lowercase().ends_with(".gif")
+}
+
+fn gif(url: String) -> MediaSource {
+    MediaSource {
+        mime_type: Some("image/gif".to_string()),
+        ..MediaSource::new(url)
+    }
+}
+
+// Describe an image on one of Reddit's hosts, preferring the MP4 rendition
+// of animated images and falling back to the GIF.
+pub fn describe(url: &Url) -> MediaDescriptor {
+    let url = &unescape(url);
+    let name = file_name(url).unwrap_or("");
+    if is_mp4_variant(url) {
+        let mut sources = vec![MediaSource {
+            mime_type: Some("video/mp4".to_string()),
+            ..MediaSource::new(url.to_string())
+        }];
+        if is_gif(name) && url.host_str() == Some("preview.redd.it") {
+            sources.push(gif(format!("https://i.redd.it/{}", name)));
+        }
+        return MediaDescriptor {
+            kind: MediaKind::Gif,
+            looping: true,
+            sources,
+            ..Default::default()
+        };
+    }
+
+    if is_gif(name) {
+        return MediaDescriptor {
+            kind: MediaKind::Gif,
+            looping: true,
+            sources: vec![gif(url.to_string())],
+            ..Default::default()
+        };
+    }
+    direct::describe(url)
+}
+
+#[async_trait]
+impl Extractor for RedditImageExtractor {
+    fn name(&self) -> &'static str {
+        "reddit image"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        url.host_str().map(|host| HOSTS.contains(&host)).unwrap_or(false)
+    }
+
+    async fn extract(&self, _client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        Ok(describe(url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe_url(url: &str) -> MediaDescriptor {
+        describe(&Url::parse(url).unwrap())
+    }
+
+    fn urls(media: &MediaDescriptor) -> Vec<&str> {
+        media.sources.iter().map(|source| source.url.as_str()).collect()
+    }
+
+    #[test]
+    fn gifs_loop() {
+        let media = describe_url("https://i.redd.it/k2x9qz0abcd81.GIF");
+        assert_eq!(media.kind, MediaKind::Gif);
+        assert!(media.looping);
+        assert_eq!(urls(&media), ["https://i.redd.it/k2x9qz0abcd81.GIF"]);
+        assert_eq!(media.sources[0].mime_type.as_deref(), Some("image/gif"));
+    }
+
+    #[test]
+    fn static_images_are_images() {
+        let media = describe_url("https://i.redd.it/k2x9qz0abcd81.jpg");
+        assert_eq!(media.kind, MediaKind::Image);
+        assert!(!media.looping);
+        assert_eq!(media.sources[0].mime_type.as_deref(),
+                   Some("image/jpeg"));
+    }
+
+    #[test]
+    fn mp4_variants_come_before_their_gif() {
+        let media = describe_url(
+            "https://preview.redd.it/k2x9qz0abcd81.gif\
+             ?format=mp4&s=0123abcd");
+        assert_eq!(media.kind, MediaKind::Gif);
+        assert!(media.looping);
+        assert_eq!(urls(&media), [
+            "https://preview.redd.it/k2x9qz0abcd81.gif?format=mp4&s=0123abcd",
+            "https://i.redd.it/k2x9qz0abcd81.gif",
+        ]);
+        assert_eq!(media.sources[0].mime_type.as_deref(), Some("video/mp4"));
+    }
+
+    #[test]
+    fn escaped_queries_are_unescaped() {
+        let media = describe_url(
+            "https://preview.redd.it/k2x9qz0abcd81.gif\
+             ?width=640&amp;format=mp4&amp;s=0123abcd");
+        assert_eq!(media.kind, MediaKind::Gif);
+        assert_eq!(media.sources[0].url,
+                   "https://preview.redd.it/k2x9qz0abcd81.gif\
+                    ?width=640&format=mp4&s=0123abcd");
+        assert_eq!(media.sources[0].mime_type.as_deref(), Some("video/mp4"));
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
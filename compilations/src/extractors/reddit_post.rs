@@ -0,0 +1,162 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            reddit_post.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Follows links to Reddit posts, such as crossposts, to the
+//                  media of the original post.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use model::MAX_CROSSPOST_DEPTH;
+use reqwest::{Client, Url};
+use serde::Deserialize;
+use super::{fetch_text, ExtractError};
+
+const HOSTS: &[&'static str] = &[
+    "reddit.com", "www.reddit.com", "old.reddit.com", "new.reddit.com",
+    "redd.it",
+];
+
+#[derive(Deserialize)]
+struct Listing {
+    data: ListingData,
+}
+
+#[derive(Deserialize)]
+struct ListingData {
+    children: Vec<Child>,
+}
+
+#[derive(Deserialize)]
+struct Child {
+    data: PostData,
+}
+
+#[derive(Deserialize)]
+struct PostData {
+    url: Option<String>,
+    #[serde(default)]
+    is_self: bool,
+
+    // The post this one was crossposted from, which carries the media.
+    #[serde(default)]
+    crosspost_parent_list: Vec<PostData>,
+}
+
+// The ID of the post, from its permalink (/r/<subreddit>/comments/<id>/...
+// or /comments/<id>) or its short link (redd.it/<id>).
+fn post_id(url: &Url) -> Option<String> {
+    if !url.host_str().map(|host| HOSTS.contains(&host)).unwrap_or(false) {
+        return None;
+    }
+
+    let segments = url.path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        ["r", _, "comments", id, ..] | ["comments", id, ..] =>
+            Some(id.to_string()),
+        [id] if url.host_str() == Some("redd.it") => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+pub fn is_post(url: &Url) -> bool {
+    post_id(url).is_some()
+}
+
+// Follow a post to the one it was crossposted from, and so on, until we get
+// to the original.
+fn original(mut post: PostData) -> PostData {
+    for _ in 0..MAX_CROSSPOST_DEPTH {
+        match post.crosspost_parent_list.is_empty() {
+            true => break,
+            false => post = post.crosspost_parent_list.swap_remove(0),
+        }
+    }
+    post
+}
+
+// Parse the post's JSON (/comments/<id>.json?raw_json=1) into the link of
+// the original post. Text posts don't link to anything.
+pub fn parse_post(json: &str) -> Result<Option<Url>, ExtractError> {
+    let listings: Vec<Listing> = serde_json::from_str(json)
+        .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+    let post = listings.into_iter().next()
+        .and_then(|listing| listing.data.children.into_iter().next())
+        .ok_or(ExtractError::NotFound)?
+        .data;
+
+    let post = original(post);
+    if post.is_self {
+        return Ok(None);
+    }
+    let url = post.url.as_deref().and_then(|url| Url::parse(url).ok());
+
+    // Links back to a post would only bring us here again.
+    Ok(url.filter(|url| !is_post(url)))
+}
+
+// The link of the original post behind a link to a Reddit post, or None if
+// it has no link of its own.
+pub async fn original_url(client: &Client, url: &Url) ->
+    Result<Option<Url>, ExtractError>
+{
+    let id = post_id(url).ok_or(ExtractError::Unsupported)?;
+
+    // Without raw_json, the URLs in the response are HTML-escaped.
+    let post = Url::parse(&format!(
+        "https://www.reddit.com/comments/{}.json?raw_json=1", id))
+        .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+    parse_post(&fetch_text(client, post).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossposts_of_crossposts_lead_to_the_original() {
+        let json = include_str!(
+            "../../fixtures/reddit/crosspost_of_crosspost.json");
+        let url = parse_post(json).unwrap().unwrap();
+        assert_eq!(url.as_str(), "https://v.redd.it/k2x9qz0abcd81");
+    }
+
+    #[test]
+    fn crossposts_of_text_posts_have_no_link() {
+        let json = include_str!(
+            "../../fixtures/reddit/self_post_crosspost.json");
+        assert!(parse_post(json).unwrap().is_none());
+    }
+
+    #[test]
+    fn crossposts_of_galleries_lead_to_the_gallery() {
+        let json = include_str!(
+            "../../fixtures/reddit/gallery_crosspost.json");
+        let url = parse_post(json).unwrap().unwrap();
+        assert_eq!(url.as_str(), "https://www.reddit.com/gallery/gx4m1");
+    }
+
+    #[test]
+    fn empty_listings_are_not_found() {
+        let json = r#"[{"kind": "Listing", "data": {"children": []}}]"#;
+        assert!(matches!(parse_post(json), Err(ExtractError::NotFound)));
+    }
+
+    #[test]
+    fn posts_are_identified_by_their_links() {
+        let id = |url| post_id(&Url::parse(url).unwrap());
+        assert_eq!(id("https://www.reddit.com/r/aww/comments/xc3p1/title/")
+                   .as_deref(), Some("xc3p1"));
+        assert_eq!(id("https://redd.it/xc3p1").as_deref(), Some("xc3p1"));
+        assert!(id("https://www.reddit.com/gallery/gx4m1").is_none());
+        assert!(id("https://v.redd.it/k2x9qz0abcd81").is_none());
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,228 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            reddit_video.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Extractor for Reddit-hosted (v.redd.it) videos.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaKind, MediaSource};
+use reqwest::{Client, Url};
+use roxmltree::{Document, Node};
+use tracing::{event, Level};
+use super::{fetch_text, hls, ExtractError, Extractor};
+
+const HOST: &'static str = "v.redd.it";
+
+// v.redd.it serves video and audio as separate DASH streams. Every
+// representation is a complete MP4 file, so rather than implementing a DASH
+// client, we hand the browser the files to play side by side. Videos without
+// a usable DASH manifest are described by their HLS playlist instead.
+pub struct RedditVideoExtractor;
+
+// Parse an ISO 8601 duration of the form used in DASH manifests, e.g.
+// "PT1M3.5S", into seconds.
+pub fn parse_duration(duration: &str) -> Option<f64> {
+    let mut time = duration.strip_prefix("PT")?;
+    let mut seconds = 0.0;
+    for (unit, scale) in [('H', 3600.0), ('M', 60.0), ('S', 1.0)] {
+        if let Some((value, rest)) = time.split_once(unit) {
+            seconds += value.parse::<f64>().ok()? * scale;
+            time = rest;
+        }
+    }
+    match time.is_empty() {
+        true => Some(seconds),
+        false => None,
+    }
+}
+
+// The base URL of the video, which DASH BaseURLs are relative to.
+fn video_base(url: &Url) -> Option<Url> {
+    let id = url.path_segments()?.find(|segment| !segment.is_empty())?;
+    Url::parse(&format!("https://{}/{}/", HOST, id)).ok()
+}
+
+// Look up an attribute on a Representation, falling back to the enclosing
+// AdaptationSet, since the manifest may specify it on either.
+fn attribute<'a>(representation: &Node<'a, '_>, name: &str) -> Option<&'a str>
+{
+    representation.attribute(name).or_else(|| {
+        representation.parent_element()
+            .filter(|parent| parent.has_tag_name("AdaptationSet"))
+            .and_then(|parent| parent.attribute(name))
+    })
+}
+
+fn is_audio(representation: &Node) -> bool {
+    attribute(representation, "contentType") == Some("audio")
+        || attribute(representation, "mimeType")
+            .map(|mime_type| mime_type.starts_with("audio/"))
+            .unwrap_or(false)
+}
+
+fn parse_representation(base: &Url, representation: &Node) ->
+    Result<MediaSource, ExtractError>
+{
+    let location = representation.children()
+        .find(|child| child.has_tag_name("BaseURL"))
+        .and_then(|child| child.text())
+        .ok_or_else(|| ExtractError::Malformed(
+            "Representation has no BaseURL".to_string()))?;
+    let url = base.join(location.trim())
+        .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+
+    Ok(MediaSource {
+        url: url.to_string(),
+        mime_type: attribute(representation, "mimeType")
+            .map(|mime_type| mime_type.to_string()),
+        width: attribute(representation, "width")
+            .and_then(|width| width.parse().ok()),
+        height: attribute(representation, "height")
+            .and_then(|height| height.parse().ok()),
+        bandwidth: attribute(representation, "bandwidth")
+            .and_then(|bandwidth| bandwidth.parse().ok()),
+        codecs: attribute(representation, "codecs")
+            .map(|codecs| codecs.to_string()),
+    })
+}
+
+// Parse the DASH manifest into a descriptor with every video representation,
+// best first, and the best audio representation.
+pub fn parse_manifest(base: &Url, manifest: &str) ->
+    Result<MediaDescriptor, ExtractError>
+{
+    let document = Document::parse(manifest)
+        .map_err(|e| ExtractError::Malformed(e.to_string()))?;
+
+    let mut sources = Vec::new();
+    let mut audio: Vec<MediaSource> = Vec::new();
+    for representation in document.descendants()
+        .filter(|node| node.has_tag_name("Representation"))
+    {
+        let source = parse_representation(base, &representation)?;
+        match is_audio(&representation) {
+            true => audio.push(source),
+            false => sources.push(source),
+        }
+    }
+
+    if sources.is_empty() {
+        return Err(ExtractError::Malformed(
+            "manifest contains no video".to_string()));
+    }
+
+    sources.sort_by_key(|source| {
+        std::cmp::Reverse((source.height, source.bandwidth))
+    });
+    let audio = audio.into_iter().max_by_key(|source| source.bandwidth);
+    let duration = document.root_element()
+        .attribute("mediaPresentationDuration")
+        .and_then(parse_duration);
+    Ok(MediaDescriptor {
+        kind: MediaKind::Video,
+        sources,
+        audio,
+        duration,
+        ..Default::default()
+    })
+}
+
+// Reddit serves GIFs uploaded to it as silent videos, which only the post
+// marks as GIFs. They're played like any other GIF.
+pub fn mark_gif(media: &mut MediaDescriptor) {
+    if media.kind == MediaKind::Video {
+        media.kind = MediaKind::Gif;
+        media.looping = true;
+    }
+}
+
+#[async_trait]
+impl Extractor for RedditVideoExtractor {
+    fn name(&self) -> &'static str {
+        "v.redd.it"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        url.host_str() == Some(HOST)
+    }
+
+    async fn extract(&self, client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let base = video_base(url).ok_or(ExtractError::Unsupported)?;
+        let join = |path| base.join(path)
+            .map_err(|e| ExtractError::Malformed(e.to_string()));
+
+        let dash = match fetch_text(client, join("DASHPlaylist.mpd")?).await {
+            Ok(manifest) => parse_manifest(&base, &manifest),
+            Err(e) => Err(e),
+        };
+        match dash {
+            Err(ExtractError::Malformed(_)) | Err(ExtractError::NotFound) => {
+                event!(Level::DEBUG, "{:?}, trying HLS", dash);
+                hls::describe(client, &join("HLSPlaylist.m3u8")?).await
+                    .or(dash)
+            },
+            dash => dash,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        video_base(&Url::parse("https://v.redd.it/k2x9qz0abcd81").unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn durations_are_read_in_seconds() {
+        assert_eq!(parse_duration("PT1M2.5S"), Some(62.5));
+        assert_eq!(parse_duration("PT0H0M4.000S"), Some(4.0));
+        assert_eq!(parse_duration("PT1H"), Some(3600.0));
+        assert_eq!(parse_duration("PT9.8S"), Some(9.8));
+        assert_eq!(parse_duration("P1D"), None);
+        assert_eq!(parse_duration("PT1X"), None);
+    }
+
+    #[test]
+    fn video_is_best_first_with_the_best_audio() {
+        let manifest = include_str!("../../fixtures/reddit/dash_manifest.mpd");
+        let media = parse_manifest(&base(), manifest).unwrap();
+        assert_eq!(media.kind, MediaKind::Video);
+        let heights = media.sources.iter()
+            .map(|source| source.height)
+            .collect::<Vec<_>>();
+        assert_eq!(heights, [Some(720), Some(360), Some(240)]);
+        assert_eq!(media.sources[0].url, base().join(
+            "DASH_720.mp4?source=fallback").unwrap().as_str());
+        assert_eq!(media.sources[0].mime_type.as_deref(), Some("video/mp4"));
+        assert_eq!(media.sources[0].bandwidth, Some(4800000));
+
+        let audio = media.audio.unwrap();
+        assert_eq!(audio.url,
+                   "https://v.redd.it/k2x9qz0abcd81/DASH_AUDIO_128.mp4");
+        assert_eq!(audio.mime_type.as_deref(), Some("audio/mp4"));
+        assert_eq!(media.duration, Some(62.5));
+    }
+
+    #[test]
+    fn manifests_without_video_are_malformed() {
+        let manifest =
+            include_str!("../../fixtures/reddit/audio_only_manifest.mpd");
+        assert!(matches!(parse_manifest(&base(), manifest),
+                         Err(ExtractError::Malformed(_))));
+        assert!(matches!(parse_manifest(&base(), "<MPD"),
+                         Err(ExtractError::Malformed(_))));
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
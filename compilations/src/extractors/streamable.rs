@@ -0,0 +1,116 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            streamable.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Extractor for Streamable videos.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use async_trait::async_trait;
+use model::{MediaDescriptor, MediaKind};
+use reqwest::{Client, Url};
+use super::page::Page;
+use super::{fetch_text, ExtractError, Extractor};
+
+const HOSTS: &[&'static str] = &["streamable.com", "www.streamable.com"];
+
+// Streamable links go to a player page, which names the MP4 file in its
+// OpenGraph tags.
+pub struct StreamableExtractor;
+
+// The canonical page of the video, given any of its URLs: the page itself,
+// or the /e/ (embed) and /o/ (original) variants.
+fn video_page(url: &Url) -> Option<Url> {
+    let segments = url.path_segments()?
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    let code = match segments.as_slice() {
+        [code] => code,
+        ["e", code] | ["o", code] => code,
+        _ => return None,
+    };
+    Url::parse(&format!("https://streamable.com/{}", code)).ok()
+}
+
+pub fn parse_page(base: &Url, html: &str) ->
+    Result<MediaDescriptor, ExtractError>
+{
+    let page = Page::parse(html);
+    let video = page.og_video(base).ok_or_else(|| ExtractError::Malformed(
+        "page has no og:video".to_string()))?;
+    Ok(MediaDescriptor {
+        kind: MediaKind::Video,
+        sources: vec![video],
+        poster: page.og_image(base).map(|image| image.url),
+        ..Default::default()
+    })
+}
+
+#[async_trait]
+impl Extractor for StreamableExtractor {
+    fn name(&self) -> &'static str {
+        "streamable"
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        url.host_str().map(|host| HOSTS.contains(&host)).unwrap_or(false)
+    }
+
+    async fn extract(&self, client: &Client, url: &Url) ->
+        Result<MediaDescriptor, ExtractError>
+    {
+        let page = video_page(url).ok_or(ExtractError::Unsupported)?;
+        let html = fetch_text(client, page.clone()).await?;
+        parse_page(&page, &html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn video_pages_name_their_mp4() {
+        let base = Url::parse("https://streamable.com/a1b2c3").unwrap();
+        let html = include_str!("../../fixtures/streamable/video_page.html");
+        let media = parse_page(&base, html).unwrap();
+        assert_eq!(media.kind, MediaKind::Video);
+        assert_eq!(media.sources.len(), 1);
+        assert_eq!(media.sources[0].url, "https://cdn-cf-east.streamable.com\
+                   /video/mp4/a1b2c3.mp4?Expires=1666224000&Signature=def");
+        assert_eq!(media.sources[0].mime_type.as_deref(), Some("video/mp4"));
+        assert_eq!(media.sources[0].width, Some(1280));
+        assert_eq!(media.sources[0].height, Some(720));
+
+        // The poster's URL is relative to the page's scheme.
+        assert_eq!(media.poster.as_deref(), Some(
+            "https://cdn-cf-east.streamable.com/image/a1b2c3.jpg\
+             ?Expires=1666224000&Signature=abc"));
+    }
+
+    #[test]
+    fn processing_pages_are_malformed() {
+        let base = Url::parse("https://streamable.com/d4e5f6").unwrap();
+        let html = include_str!(
+            "../../fixtures/streamable/processing_page.html");
+        assert!(matches!(parse_page(&base, html),
+                         Err(ExtractError::Malformed(_))));
+    }
+
+    #[test]
+    fn embeds_lead_to_the_video_page() {
+        let page = |url| video_page(&Url::parse(url).unwrap())
+            .map(|page| page.to_string());
+        let expected = Some("https://streamable.com/a1b2c3".to_string());
+        assert_eq!(page("https://streamable.com/a1b2c3"), expected);
+        assert_eq!(page("https://streamable.com/e/a1b2c3"), expected);
+        assert_eq!(page("https://www.streamable.com/o/a1b2c3/"), expected);
+        assert_eq!(page("https://streamable.com/"), None);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
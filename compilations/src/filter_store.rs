@@ -0,0 +1,112 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            filter_store.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Each user's filter rules, kept across sessions.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use model::{Filter, FilterRules};
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::{event, Level};
+use crate::configuration::FilterConfiguration;
+
+struct Inner {
+    path: Option<PathBuf>,
+    filters: Mutex<HashMap<String, Arc<Filter>>>,
+
+    // Serializes writes to the file.
+    writer: AsyncMutex<()>,
+}
+
+// Filter rules, keyed by Reddit username.
+#[derive(Clone)]
+pub struct FilterStore {
+    inner: Arc<Inner>,
+}
+
+impl FilterStore {
+    pub fn new(configuration: &FilterConfiguration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                path: configuration.path.as_ref().map(PathBuf::from),
+                filters: Mutex::new(HashMap::new()),
+                writer: AsyncMutex::new(()),
+            }),
+        }
+    }
+
+    // The user's filter, if they've configured one.
+    pub fn get(&self, username: &str) -> Option<Arc<Filter>> {
+        self.inner.filters.lock().unwrap().get(username).cloned()
+    }
+
+    pub async fn set(&self, username: &str, filter: Filter) ->
+        io::Result<()>
+    {
+        self.inner.filters.lock().unwrap()
+            .insert(username.to_string(), Arc::new(filter));
+        self.persist().await
+    }
+
+    // Read the rules from disk. Rules that no longer compile are dropped.
+    pub async fn load(&self) -> io::Result<()> {
+        let path = match &self.inner.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let contents = match tokio::fs::read(path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let loaded: HashMap<String, FilterRules> =
+            serde_json::from_slice(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut filters = self.inner.filters.lock().unwrap();
+        for (username, rules) in loaded {
+            match Filter::new(rules) {
+                Ok(filter) => { filters.insert(username, Arc::new(filter)); },
+                Err(e) => event!(Level::WARN, "Dropping filter of {}: {}",
+                                 username, e),
+            }
+        }
+        event!(Level::INFO, "Loaded filters of {} users", filters.len());
+        Ok(())
+    }
+
+    async fn persist(&self) -> io::Result<()> {
+        let path = match &self.inner.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let _writer = self.inner.writer.lock().await;
+        let contents = {
+            let filters = self.inner.filters.lock().unwrap();
+            let rules = filters.iter()
+                .map(|(username, filter)| (username, filter.rules()))
+                .collect::<HashMap<_, _>>();
+            serde_json::to_vec(&rules)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        };
+
+        // Write to a temporary file first, so a crash never leaves a
+        // partially written file behind.
+        let temporary = path.with_extension("tmp");
+        tokio::fs::write(&temporary, contents).await?;
+        tokio::fs::rename(&temporary, path).await
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
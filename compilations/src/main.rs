@@ -7,11 +7,12 @@
 //
 // CREATED:         05/23/2022
 //
-// LAST EDITED:     06/20/2022
+// LAST EDITED:     10/19/2026
 ////
 
 use std::error::Error;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
     body::{self, Empty, Full}, http::{header::{self, HeaderValue}, StatusCode},
@@ -27,20 +28,40 @@ use oauth2::{
     AuthUrl, basic::BasicClient, ClientId, ClientSecret, RedirectUrl, TokenUrl,
 };
 use tower_http::trace::TraceLayer;
+use tracing::{event, Level};
 
 mod api;
 mod configuration;
+mod duplicates;
 mod endpoints;
+mod extraction_cache;
 mod extractor;
+mod extractors;
+mod filter_store;
+mod media_proxy;
+mod ordering;
+mod public_address;
 mod rate_limit;
 mod resolver;
+mod response_cache;
+mod retry;
+mod validation;
 
 use configuration::{load_secret, load_configuration};
+use duplicates::Identifier;
 use endpoints::{login, redirect_callback};
+use extraction_cache::ExtractionCache;
+use extractors::Registry;
+use filter_store::FilterStore;
+use media_proxy::MediaProxy;
+use ordering::OrderedListings;
 use rate_limit::RateLimiter;
 use resolver::ResolverBuilder;
+use response_cache::ResponseCache;
+use validation::Validator;
 
 const APP_URL: &'static str = "/app";
+const MEDIA_URL: &'static str = "/media";
 const REDIRECT_URL: &'static str = "/callback";
 const AUTH_URL: &'static str = "https://www.reddit.com/api/v1/authorize";
 const TOKEN_URL: &'static str = "https://www.reddit.com/api/v1/access_token";
@@ -48,6 +69,9 @@ pub(crate) const USER_AGENT: &'static str =
     "edtwardy-savedapi/1.0;Ethan D. Twardy <ethan.twardy@gmail.com>";
 pub(crate) const REDDIT_BASE: &'static str = "https://oauth.reddit.com";
 pub(crate) const CSRF_TOKEN_KEY: &'static str = "csrf_token";
+pub(crate) const SCOPES_KEY: &'static str = "scopes";
+pub(crate) const USERNAME_KEY: &'static str = "username";
+const DEFAULT_RESPONSE_CACHE_TTL: u64 = 30;
 
 static FRONTEND_DIR: Dir<'_> =
     include_dir!("$CARGO_MANIFEST_DIR/../frontend/dist");
@@ -102,6 +126,19 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let session_store = AxumSessionStore::new(None, session_config);
 
     let (rate_limiter, responder) = RateLimiter::new();
+    let response_cache = ResponseCache::new(Duration::from_secs(
+        configuration.response_cache_ttl
+            .unwrap_or(DEFAULT_RESPONSE_CACHE_TTL)));
+
+    let extraction_cache = ExtractionCache::new(
+        &configuration.extraction_cache.unwrap_or_default());
+    if let Err(e) = extraction_cache.load().await {
+        // Not fatal: we can always extract the media again.
+        event!(Level::WARN, "Couldn't load extraction cache: {:?}", e);
+    }
+    let mut registry = Registry::with_defaults()?;
+    registry.set_cache(extraction_cache.clone());
+    let registry = Arc::new(registry);
 
     let resolver = Arc::new(
         ResolverBuilder::default()
@@ -109,9 +146,31 @@ async fn main() -> Result<(), Box<dyn Error>> {
             .script_name(configuration.script_name.clone())
             .route("redirect".to_string(), REDIRECT_URL.to_string())
             .route("app".to_string(), APP_URL.to_string())
+            .route("media".to_string(), MEDIA_URL.to_string())
             .build()?
     );
 
+    let media_proxy = match &configuration.media_proxy {
+        Some(proxy) => Some(MediaProxy::new(
+            proxy, resolver.get("media").unwrap())?),
+        None => None,
+    };
+
+    let validator = match &configuration.media_validation {
+        Some(validation) => Some(Arc::new(Validator::new(validation)?)),
+        None => None,
+    };
+
+    let filter_store = FilterStore::new(
+        &configuration.filters.unwrap_or_default());
+    if let Err(e) = filter_store.load().await {
+        event!(Level::WARN, "Couldn't load filters: {:?}", e);
+    }
+    let media_service = api::MediaService {
+        registry, validator, proxy: media_proxy.clone(),
+        filters: filter_store.clone(),
+    };
+
     let client = Arc::new(BasicClient::new(
         ClientId::new(secret.id.to_string()),
         Some(ClientSecret::new(secret.secret)),
@@ -120,7 +179,10 @@ async fn main() -> Result<(), Box<dyn Error>> {
     ).set_redirect_uri(RedirectUrl::new(
         resolver.get_full("redirect").unwrap())?));
 
-    let app = Router::new()
+    let ordered_listings = OrderedListings::default();
+    let identifier = Identifier::new()?;
+    let app = api::proxy_routes(
+        Router::new(), rate_limiter.clone(), response_cache)
         .route("/login", get({
             let client = client.clone();
             move |session| { login(session, client) }
@@ -133,28 +195,61 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 redirect_callback(params, session, client, resolver)
             }
         }))
-        .route("/api/v1/me", get({
-            let rate_limiter = rate_limiter.clone();
-            move |params, session| {
-                let path = "/api/v1/me".to_string();
-                api::proxy_reddit_get(path, params, session, rate_limiter)
+        .route("/video", post({
+            let service = media_service.clone();
+            move |preference, session, request| {
+                api::get_video_url(preference, session, request, service)
             }
         }))
-        .route("/user/:username/saved", get({
-            let rate_limiter = rate_limiter.clone();
-            move |Path(username): Path<String>, params, session| {
-                let path = "/user/".to_string() + &username + "/saved";
-                api::proxy_reddit_get(path, params, session, rate_limiter)
+        .route("/video/batch", post({
+            let service = media_service.clone();
+            move |preference, session, requests| {
+                api::get_video_batch(preference, session, requests, service)
             }
         }))
-        .route("/api/unsave", post({
+        .route("/ordered", post({
             let rate_limiter = rate_limiter.clone();
-            move |params, session| {
-                let path = "/api/unsave".to_string();
-                api::proxy_reddit_post(path, params, session, rate_limiter)
+            let listings = ordered_listings.clone();
+            move |page, session, request| {
+                api::create_ordered(page, session, request, rate_limiter,
+                                    listings)
+            }
+        }))
+        .route("/ordered/:cursor", get({
+            let listings = ordered_listings.clone();
+            move |cursor, page, session| {
+                api::get_ordered_page(cursor, page, session, listings)
+            }
+        }))
+        .route("/duplicates", post({
+            let identifier = identifier.clone();
+            move |session, requests| {
+                api::identify_posts(session, requests, identifier)
             }
         }))
-        .route("/video", post(api::get_video_url))
+        .route("/filters", get({
+            let store = filter_store.clone();
+            move |session| api::get_filters(session, store)
+        }).put({
+            let store = filter_store.clone();
+            move |session, rules| api::put_filters(session, rules, store)
+        }))
+        .route("/video/stats", get({
+            let cache = extraction_cache.clone();
+            move || api::get_extraction_stats(cache)
+        }));
+
+    // The media proxy is only served if it's been configured.
+    let app = match media_proxy {
+        Some(proxy) => app.route(
+            &(MEDIA_URL.to_string() + "/:token"),
+            get(move |token, headers, session| {
+                api::get_media(token, headers, session, proxy)
+            })),
+        None => app,
+    };
+
+    let app = app
         .layer(AxumSessionLayer::new(session_store))
         .layer(TraceLayer::new_for_http())
         ;
@@ -170,6 +265,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         },
     };
 
+    tokio::spawn(extraction_cache.spawn());
     tokio::select! {
         result = responder.spawn() => { result.unwrap() }
         result = server => { result.unwrap() }
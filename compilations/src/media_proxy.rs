@@ -0,0 +1,343 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            media_proxy.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Streams media from hosts that the browser can't load it
+//                  from directly.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::{
+    body::{self, Bytes, StreamBody},
+    http::{header::{self, HeaderMap, HeaderValue}, StatusCode},
+    response::Response,
+};
+use futures_util::{Stream, StreamExt};
+use model::{MediaDescriptor, MediaSource};
+use reqwest::{Client, Url};
+use ring::{digest, hmac, rand::SystemRandom};
+use serde::{Serialize, Deserialize};
+use tokio::time::{self, Duration, Instant};
+use tracing::{event, Level};
+use crate::configuration::MediaProxyConfiguration;
+use crate::public_address;
+use crate::USER_AGENT;
+
+// Seconds a /media URL stays valid, by default. Long enough to watch a
+// video, short enough that a leaked URL is of little use.
+const DEFAULT_TOKEN_TTL: u64 = 60 * 60;
+
+// Request headers relayed to the media host.
+const REQUEST_HEADERS: &[header::HeaderName] = &[
+    header::RANGE, header::IF_RANGE, header::IF_NONE_MATCH,
+    header::IF_MODIFIED_SINCE,
+];
+
+// Response headers relayed to the client. Everything else (cookies, CORS,
+// CSP, redirects) is the media host's business, not ours.
+const RESPONSE_HEADERS: &[header::HeaderName] = &[
+    header::CONTENT_TYPE, header::CONTENT_LENGTH, header::CONTENT_RANGE,
+    header::ACCEPT_RANGES, header::ETAG, header::LAST_MODIFIED,
+];
+
+// Types of content that may be served from our origin. Anything else, HTML
+// in particular, could be used to run script as us.
+const MEDIA_TYPES: &[&'static str] = &["video/", "audio/", "image/"];
+
+// What a /media token grants: one user access to one URL, for a while.
+#[derive(Serialize, Deserialize)]
+struct Grant {
+    url: String,
+    user: String,
+
+    // Seconds since the epoch
+    expires: u64,
+}
+
+// Bytes a user may stream, refilled at the configured rate. The balance may
+// go negative, in which case the user's streams wait until it recovers.
+struct Bucket {
+    balance: f64,
+    updated: Instant,
+}
+
+#[derive(Clone)]
+struct BandwidthLimiter {
+    // Bytes per second
+    rate: f64,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+#[derive(Clone)]
+pub struct MediaProxy {
+    key: hmac::Key,
+    hosts: Vec<String>,
+    token_ttl: u64,
+
+    // Path of the /media route, including the script name.
+    path: String,
+    client: Client,
+    limiter: Option<BandwidthLimiter>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or(0)
+}
+
+fn encode(data: &[u8]) -> String {
+    base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+}
+
+fn decode(data: &str) -> Option<Vec<u8>> {
+    base64::decode_config(data, base64::URL_SAFE_NO_PAD).ok()
+}
+
+// Whether the URL is on one of the hosts, or a subdomain of one.
+fn is_on(hosts: &[String], url: &Url) -> bool {
+    let host = match url.host_str() {
+        Some(host) => host,
+        None => return false,
+    };
+    hosts.iter().any(|domain| {
+        host == domain || host.ends_with(&format!(".{}", domain))
+    })
+}
+
+// An opaque, stable identifier for the holder of an access token.
+pub fn user_id(token_secret: &str) -> String {
+    encode(digest::digest(&digest::SHA256, token_secret.as_bytes()).as_ref())
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// BandwidthLimiter
+////
+
+impl BandwidthLimiter {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate: rate as f64,
+            buckets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    // Account for bytes sent to the user, waiting if they're over the limit.
+    async fn consume(&self, user: &str, bytes: usize) {
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap();
+            let now = Instant::now();
+            let rate = self.rate;
+            let bucket = buckets.entry(user.to_string())
+                .or_insert(Bucket { balance: rate, updated: now });
+
+            // Allow a burst of up to a second's worth.
+            let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+            bucket.balance = (bucket.balance + elapsed * rate).min(rate);
+            bucket.updated = now;
+            bucket.balance -= bytes as f64;
+            match bucket.balance < 0.0 {
+                true => Some(Duration::from_secs_f64(-bucket.balance / rate)),
+                false => None,
+            }
+        };
+
+        if let Some(wait) = wait {
+            time::sleep(wait).await;
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// MediaProxy
+////
+
+impl MediaProxy {
+    pub fn new(configuration: &MediaProxyConfiguration, path: String) ->
+        Result<Self, Box<dyn std::error::Error>>
+    {
+        // Tokens are short-lived, so a new key on every start is fine.
+        let key = hmac::Key::generate(hmac::HMAC_SHA256, &SystemRandom::new())
+            .map_err(|_| "couldn't generate a media proxy key")?;
+        // Any logged in user can have any URL on a proxied host proxied, so
+        // proxying every host would make us an open relay.
+        if configuration.hosts.iter().any(|host| host == "*") {
+            return Err("the media proxy can't proxy every host".into());
+        }
+
+        // Redirects are followed only as far as they stay on proxied hosts.
+        let hosts = configuration.hosts.clone();
+        let client = public_address::client_builder()
+            .redirect(public_address::redirect_policy(
+                move |url| is_on(&hosts, url)))
+            .user_agent(USER_AGENT)
+            .build()?;
+        Ok(Self {
+            key,
+            hosts: configuration.hosts.clone(),
+            token_ttl: configuration.token_ttl.unwrap_or(DEFAULT_TOKEN_TTL),
+            path,
+            client,
+            limiter: configuration.bandwidth_limit.map(BandwidthLimiter::new),
+        })
+    }
+
+    fn is_proxied(&self, url: &Url) -> bool {
+        is_on(&self.hosts, url)
+    }
+
+    fn sign(&self, grant: &Grant) -> String {
+        let payload = encode(&serde_json::to_vec(grant).unwrap());
+        let signature = hmac::sign(&self.key, payload.as_bytes());
+        payload + "." + &encode(signature.as_ref())
+    }
+
+    // The grant in the token, if it's one of ours and still valid.
+    fn verify(&self, token: &str) -> Option<Grant> {
+        let (payload, signature) = token.split_once('.')?;
+        hmac::verify(&self.key, payload.as_bytes(), &decode(signature)?)
+            .ok()?;
+        let grant: Grant = serde_json::from_slice(&decode(payload)?).ok()?;
+        match grant.expires > now() {
+            true => Some(grant),
+            false => None,
+        }
+    }
+
+    fn rewrite_source(&self, source: &mut MediaSource, user: &str) {
+        // HLS playlists refer to their segments by URL, which we'd have to
+        // rewrite too. Those are played directly.
+        let is_playlist = source.mime_type.as_deref()
+            .map(|mime_type| mime_type.contains("mpegurl"))
+            .unwrap_or(false);
+        if !is_playlist {
+            source.url = self.rewrite_url(&source.url, user);
+        }
+    }
+
+    fn rewrite_url(&self, url: &str, user: &str) -> String {
+        match Url::parse(url) {
+            Ok(parsed) if self.is_proxied(&parsed) => {
+                let grant = Grant {
+                    url: url.to_string(),
+                    user: user.to_string(),
+                    expires: now() + self.token_ttl,
+                };
+                format!("{}/{}", self.path, self.sign(&grant))
+            },
+            _ => url.to_string(),
+        }
+    }
+
+    // Point the media's URLs on proxied hosts at /media instead.
+    pub fn rewrite(&self, media: &mut MediaDescriptor, user: &str) {
+        for source in media.sources.iter_mut().chain(media.audio.as_mut()) {
+            self.rewrite_source(source, user);
+        }
+        if let Some(poster) = media.poster.as_mut() {
+            *poster = self.rewrite_url(poster, user);
+        }
+        for item in media.items.iter_mut() {
+            self.rewrite(item, user);
+        }
+    }
+
+    // Fetch the media named by the token on behalf of the user, passing
+    // Range and conditional request headers through.
+    pub async fn fetch(&self, token: &str, user: &str, headers: &HeaderMap) ->
+        Result<Response, StatusCode>
+    {
+        let grant = self.verify(token).ok_or(StatusCode::FORBIDDEN)?;
+        if grant.user != user {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        let url = Url::parse(&grant.url).map_err(|e| {
+            event!(Level::ERROR, "{:?}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+
+        // Hosts that check the Referer generally want to see themselves.
+        let mut request = self.client.get(url.clone())
+            .header(header::REFERER, url.origin().ascii_serialization() + "/");
+        for name in REQUEST_HEADERS {
+            if let Some(value) = headers.get(name) {
+                request = request.header(name, value.clone());
+            }
+        }
+        let response = request.send().await.map_err(|e| {
+            event!(Level::ERROR, "{:?}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+        // Only the status of an error is passed on. The body is the host's
+        // error page, which has no business being served from our origin.
+        let status = response.status();
+        if !status.is_success() && status != StatusCode::NOT_MODIFIED {
+            event!(Level::DEBUG, "{} from {}", status, url);
+            return Err(match status.is_redirection() {
+                true => StatusCode::BAD_GATEWAY,
+                false => status,
+            });
+        }
+
+        let content_type = response.headers().get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+        let is_media = MEDIA_TYPES.iter()
+            .any(|prefix| content_type.starts_with(prefix));
+        if status.is_success() && !is_media {
+            event!(Level::WARN, "Refusing to proxy {} from {}", content_type,
+                   url);
+            return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+
+        let mut builder = Response::builder().status(status);
+        let response_headers = builder.headers_mut().unwrap();
+        for name in RESPONSE_HEADERS {
+            if let Some(value) = response.headers().get(name) {
+                response_headers.insert(name, value.clone());
+            }
+        }
+        response_headers.insert(
+            header::CACHE_CONTROL, HeaderValue::from_static("private"));
+        response_headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"));
+
+        let body = self.throttle(response.bytes_stream(), user.to_string());
+        builder
+            .body(body::boxed(StreamBody::new(body)))
+            .map_err(|e| {
+                event!(Level::ERROR, "{:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })
+    }
+
+    // Hold each chunk back until the user's bandwidth allows for it.
+    fn throttle<S>(&self, stream: S, user: String) ->
+        impl Stream<Item = reqwest::Result<Bytes>>
+    where S: Stream<Item = reqwest::Result<Bytes>>
+    {
+        let limiter = self.limiter.clone();
+        stream.then(move |chunk| {
+            let limiter = limiter.clone();
+            let user = user.clone();
+            async move {
+                if let (Some(limiter), Ok(bytes)) = (&limiter, &chunk) {
+                    limiter.consume(&user, bytes.len()).await;
+                }
+                chunk
+            }
+        })
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
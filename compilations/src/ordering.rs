@@ -0,0 +1,369 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            ordering.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Compilations fetched in full, so that they can be played
+//                  in an order other than Reddit's.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+use model::{ListingSource, OrderedRequest, PostOrder, MAX_SOURCES};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use reqwest_middleware::ClientWithMiddleware;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::time::{Duration, Instant};
+use tracing::{event, Level};
+use crate::api::is_path_name;
+use crate::rate_limit::{Priority, RateLimiter};
+use crate::REDDIT_BASE;
+
+// Reddit stops listing posts at about a thousand, however far back they go.
+const MAX_POSTS: usize = 1000;
+
+// Most posts Reddit returns in one page of a listing.
+const LISTING_PAGE_SIZE: usize = 100;
+
+pub const DEFAULT_PAGE_SIZE: usize = 25;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+// How long fetched listings, and the orders made from them, are kept.
+const TTL: Duration = Duration::from_secs(60 * 60);
+
+// Most fetched listings, and most ordered compilations, kept for a user.
+// Making another drops the user's oldest.
+const MAX_PER_USER: usize = 4;
+
+type Posts = Arc<Vec<Value>>;
+
+// One page of an ordered compilation.
+#[derive(Serialize)]
+pub struct OrderedPage {
+    // Names the compilation in later requests for its pages
+    pub cursor: String,
+
+    // The seed it was shuffled with, to play the same order again
+    pub seed: Option<u32>,
+    pub total: usize,
+    pub posts: Vec<Value>,
+
+    // Offset of the next page, if there is one
+    pub next: Option<usize>,
+}
+
+struct Ordered {
+    user: String,
+    expires: Instant,
+    seed: Option<u32>,
+    posts: Posts,
+}
+
+#[derive(Default)]
+struct Inner {
+    // The posts of each user's compilations, in Reddit's order, keyed by
+    // the sources they were fetched from.
+    listings: HashMap<(String, String), (Instant, Posts)>,
+
+    // Compilations that have been put in order, by cursor.
+    ordered: HashMap<String, Ordered>,
+}
+
+#[derive(Clone, Default)]
+pub struct OrderedListings {
+    inner: Arc<Mutex<Inner>>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Ordering
+////
+
+fn get_path<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, key| match key.parse::<usize>() {
+        Ok(index) => value.get(index),
+        Err(_) => value.get(key),
+    })
+}
+
+fn name(post: &Value) -> Option<&str> {
+    get_path(post, &["data", "name"])?.as_str()
+}
+
+fn score(post: &Value) -> i64 {
+    get_path(post, &["data", "score"]).and_then(Value::as_i64).unwrap_or(0)
+}
+
+fn subreddit(post: &Value) -> String {
+    get_path(post, &["data", "subreddit"])
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+// Length of a Reddit-hosted video, which is the only kind the listing
+// tells us about. Crossposts carry the video of the original.
+fn duration(post: &Value) -> Option<f64> {
+    let data = post.get("data")?;
+    let original = get_path(data, &["crosspost_parent_list", "0"]);
+    [Some(data), original].into_iter().flatten()
+        .flat_map(|data| ["secure_media", "media"].into_iter()
+                  .map(move |key| (data, key)))
+        .find_map(|(data, key)| {
+            get_path(data, &[key, "reddit_video", "duration"])?.as_f64()
+        })
+}
+
+// Put the posts in order. Sorting is stable, so posts that compare equal
+// keep Reddit's order.
+fn sort(posts: &mut [Value], order: PostOrder, seed: Option<u32>) {
+    match order {
+        PostOrder::Listing => {},
+        PostOrder::Reversed => posts.reverse(),
+        PostOrder::Shuffle => {
+            let mut rng = StdRng::seed_from_u64(
+                seed.unwrap_or_default().into());
+            posts.shuffle(&mut rng);
+        },
+        PostOrder::Score => posts.sort_by_key(|post| -score(post)),
+        PostOrder::Subreddit => posts.sort_by_key(subreddit),
+        PostOrder::Duration => posts.sort_by(|a, b| {
+            match (duration(a), duration(b)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            }
+        }),
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Fetching
+////
+
+// The OAuth scope needed to read the listing.
+pub fn required_scope(source: &ListingSource) -> &'static str {
+    match source {
+        ListingSource::Saved { .. } | ListingSource::Upvoted { .. }
+            | ListingSource::Submitted { .. } => "history",
+        _ => "read",
+    }
+}
+
+// Every post of the listing, as far back as Reddit will go. That's up to ten
+// pages, so they're fetched in the background class, where they don't hold
+// up other users' interactive requests.
+async fn fetch_listing(
+    client: &ClientWithMiddleware, rate_limiter: &mut RateLimiter,
+    source: &ListingSource,
+) -> Result<Vec<Value>, StatusCode>
+{
+    let url = REDDIT_BASE.to_string() + &source.path();
+    let mut posts = Vec::new();
+    let mut after: Option<String> = None;
+    while posts.len() < MAX_POSTS {
+        let mut query = source.query();
+        query.push(("limit", LISTING_PAGE_SIZE.to_string()));
+        if let Some(after) = &after {
+            query.push(("after", after.clone()));
+            query.push(("count", posts.len().to_string()));
+        }
+
+        let request = client.get(&url).query(&query);
+        let response = rate_limiter
+            .send(client, request, Priority::Background).await
+            .map_err(|e| {
+                event!(Level::ERROR, "{:?}", e);
+                StatusCode::BAD_GATEWAY
+            })?;
+        if !response.status().is_success() {
+            return Err(response.status());
+        }
+        let body = response.bytes().await.map_err(|e| {
+            event!(Level::ERROR, "{:?}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+        let page: Value = serde_json::from_slice(&body).map_err(|e| {
+            event!(Level::ERROR, "{:?}", e);
+            StatusCode::BAD_GATEWAY
+        })?;
+
+        let children = get_path(&page, &["data", "children"])
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        posts.extend(children);
+        after = get_path(&page, &["data", "after"])
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        if after.is_none() {
+            break;
+        }
+    }
+    posts.truncate(MAX_POSTS);
+    Ok(posts)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// OrderedListings
+////
+
+// Remove a user's entries from the map, oldest first, until there's room for
+// one more. expiry is the expiry of the entries that are the user's.
+fn make_room<K, V>(map: &mut HashMap<K, V>,
+                   expiry: impl Fn(&K, &V) -> Option<Instant>)
+where K: Clone + Eq + std::hash::Hash
+{
+    let mut entries = map.iter()
+        .filter_map(|(k, v)| expiry(k, v).map(|expires| (expires, k.clone())))
+        .collect::<Vec<_>>();
+    if entries.len() < MAX_PER_USER {
+        return;
+    }
+    entries.sort_by_key(|(expires, _)| *expires);
+    for (_, k) in entries.iter().take(entries.len() + 1 - MAX_PER_USER) {
+        map.remove(k);
+    }
+}
+
+impl Inner {
+    fn purge(&mut self, now: Instant) {
+        self.listings.retain(|_, (expires, _)| *expires > now);
+        self.ordered.retain(|_, ordered| ordered.expires > now);
+    }
+}
+
+impl OrderedListings {
+    // The posts of the sources, in Reddit's order, fetched once and then
+    // reused until they expire. Posts listed by more than one source are
+    // only kept the first time. A source that fails is left out, unless
+    // they all fail.
+    async fn fetch(
+        &self, client: &ClientWithMiddleware, rate_limiter: &mut RateLimiter,
+        user: &str, sources: &[ListingSource],
+    ) -> Result<Posts, StatusCode>
+    {
+        let key = (user.to_string(), serde_json::to_string(sources).unwrap());
+        let now = Instant::now();
+        if let Some((expires, posts)) =
+            self.inner.lock().unwrap().listings.get(&key)
+        {
+            if *expires > now {
+                return Ok(posts.clone());
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut posts = Vec::new();
+        let mut failed = 0;
+        let mut error = StatusCode::BAD_GATEWAY;
+        for source in sources {
+            let listing = match fetch_listing(client, rate_limiter, source)
+                .await
+            {
+                Ok(listing) => listing,
+                Err(status) => {
+                    event!(Level::WARN, "{}: {}", source.path(), status);
+                    failed += 1;
+                    error = status;
+                    continue;
+                },
+            };
+            posts.extend(listing.into_iter().filter(|post| {
+                name(post).map(|name| seen.insert(name.to_string()))
+                    .unwrap_or(true)
+            }));
+        }
+        if failed > 0 && failed == sources.len() {
+            return Err(error);
+        }
+
+        // Failures may not last, so the listings are fetched again next time
+        // if any of them failed.
+        let posts = Arc::new(posts);
+        if failed == 0 {
+            let mut inner = self.inner.lock().unwrap();
+            make_room(&mut inner.listings, |(owner, _), (expires, _)| {
+                (owner == user).then_some(*expires)
+            });
+            inner.listings.insert(key, (Instant::now() + TTL, posts.clone()));
+        }
+        Ok(posts)
+    }
+
+    // Put the user's compilation in order, and hand out its first page.
+    pub async fn create(
+        &self, client: &ClientWithMiddleware, mut rate_limiter: RateLimiter,
+        user: &str, request: OrderedRequest, page_size: usize,
+    ) -> Result<OrderedPage, StatusCode>
+    {
+        if request.sources.len() > MAX_SOURCES {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        // The names go in the paths of the listings upstream.
+        if !request.sources.iter()
+            .flat_map(ListingSource::names)
+            .all(is_path_name)
+        {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        let posts = self.fetch(client, &mut rate_limiter, user,
+                               &request.sources).await?;
+        let seed = match request.order {
+            PostOrder::Shuffle => Some(request.seed.unwrap_or_else(
+                || rand::thread_rng().gen())),
+            _ => None,
+        };
+        let mut posts = (*posts).clone();
+        sort(&mut posts, request.order, seed);
+
+        let cursor = format!("{:016x}", rand::thread_rng().gen::<u64>());
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.purge(now);
+        make_room(&mut inner.ordered, |_, ordered| {
+            (ordered.user == user).then_some(ordered.expires)
+        });
+        inner.ordered.insert(cursor.clone(), Ordered {
+            user: user.to_string(),
+            expires: now + TTL,
+            seed,
+            posts: Arc::new(posts),
+        });
+        drop(inner);
+        self.page(&cursor, user, 0, page_size)
+    }
+
+    // A page of a compilation that was put in order by create. Each user
+    // only gets to see their own.
+    pub fn page(&self, cursor: &str, user: &str, offset: usize,
+                page_size: usize) -> Result<OrderedPage, StatusCode>
+    {
+        let inner = self.inner.lock().unwrap();
+        let ordered = inner.ordered.get(cursor)
+            .filter(|ordered| ordered.user == user)
+            .filter(|ordered| ordered.expires > Instant::now())
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+        let total = ordered.posts.len();
+        let start = offset.min(total);
+        let end = (start + page_size.clamp(1, MAX_PAGE_SIZE)).min(total);
+        Ok(OrderedPage {
+            cursor: cursor.to_string(),
+            seed: ordered.seed,
+            total,
+            posts: ordered.posts[start..end].to_vec(),
+            next: match end < total {
+                true => Some(end),
+                false => None,
+            },
+        })
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
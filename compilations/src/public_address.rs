@@ -0,0 +1,199 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            public_address.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Keeps requests for user-supplied URLs on the public
+//                  internet, away from this host and its network.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::{redirect, ClientBuilder, Url};
+
+// Redirects followed before giving up, as reqwest does by default.
+const MAX_REDIRECTS: usize = 10;
+
+// Returned when a URL leads somewhere that isn't on the public internet.
+#[derive(Debug)]
+pub struct NotPublic(pub String);
+
+// Resolves host names like the system resolver does, but only hands out the
+// addresses that are public. Every connection to a named host is resolved
+// by it, including the ones made to follow redirects, so it can't be talked
+// around by a host that redirects, or whose name resolves differently the
+// second time. Hosts that are addresses aren't resolved at all, so URLs
+// have to be checked with is_public_url before they're requested.
+pub struct PublicResolver;
+
+///////////////////////////////////////////////////////////////////////////////
+// Addresses
+////
+
+fn is_public_v4(ip: &Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified() || ip.is_loopback() || ip.is_private()
+      || ip.is_link_local() || ip.is_broadcast() || ip.is_multicast()
+      || ip.is_documentation()
+      // "This network", and IETF protocol assignments
+      || a == 0 || (a == 192 && b == 0 && c == 0)
+      // Shared address space (carrier-grade NAT)
+      || (a == 100 && (64..128).contains(&b))
+      // Benchmarking, and reserved for future use
+      || (a == 198 && (18..20).contains(&b)) || a >= 240)
+}
+
+fn is_public_v6(ip: &Ipv6Addr) -> bool {
+    if let Some(ip) = ip.to_ipv4_mapped() {
+        return is_public_v4(&ip);
+    }
+    let first = ip.segments()[0];
+    !(ip.is_unspecified() || ip.is_loopback() || ip.is_multicast()
+      // Unique local, and link-local
+      || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+      // Documentation
+      || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
+// Whether the address is one anybody on the internet could reach, rather
+// than one of ours: loopback, link-local (which is where cloud metadata
+// services live), private networks and the like are not.
+pub fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+// Whether the URL may be requested on a user's behalf, as far as can be
+// told without resolving its host: it's HTTP(S), and its host isn't an
+// address that isn't public. Host names are checked by PublicResolver.
+pub fn is_public_url(url: &Url) -> bool {
+    let scheme_ok = url.scheme() == "http" || url.scheme() == "https";
+    let host_ok = match url.host_str() {
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']')
+            .parse::<IpAddr>()
+            .map(|ip| is_public(&ip))
+            .unwrap_or(true),
+        None => false,
+    };
+    scheme_ok && host_ok
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// NotPublic
+////
+
+impl fmt::Display for NotPublic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not a public address", self.0)
+    }
+}
+
+impl std::error::Error for NotPublic {}
+
+///////////////////////////////////////////////////////////////////////////////
+// PublicResolver
+////
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addresses = tokio::net::lookup_host((host.as_str(), 0)).await?
+                .filter(|address: &SocketAddr| is_public(&address.ip()))
+                .collect::<Vec<_>>();
+            if addresses.is_empty() {
+                return Err(Box::new(NotPublic(host)) as _);
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Clients
+////
+
+// Follows redirects the way reqwest does by default, except to URLs that
+// is_public_url refuses, which never reach the resolver, or that the caller
+// doesn't allow.
+pub fn redirect_policy<F>(allowed: F) -> redirect::Policy
+where F: Fn(&Url) -> bool + Send + Sync + 'static
+{
+    redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if !is_public_url(attempt.url()) || !allowed(attempt.url()) {
+            let url = attempt.url().to_string();
+            attempt.error(NotPublic(url))
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+// A client builder for requests to URLs that came from users, which can
+// only reach public addresses, given URLs that pass is_public_url.
+pub fn client_builder() -> ClientBuilder {
+    reqwest::Client::builder()
+        .dns_resolver(std::sync::Arc::new(PublicResolver))
+        .redirect(redirect_policy(|_| true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public(&ip.parse().unwrap())
+    }
+
+    fn public_url(url: &str) -> bool {
+        is_public_url(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn internal_addresses_are_not_public() {
+        for ip in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1",
+                   "169.254.169.254", "0.0.0.0", "100.64.0.1",
+                   "255.255.255.255", "::1", "::", "fe80::1", "fd00::1",
+                   "::ffff:127.0.0.1", "::ffff:169.254.169.254"]
+        {
+            assert!(!public(ip), "{} should not be public", ip);
+        }
+    }
+
+    #[test]
+    fn internet_addresses_are_public() {
+        for ip in ["151.101.1.140", "8.8.8.8", "2606:4700::6810:84e5",
+                   "::ffff:151.101.1.140"]
+        {
+            assert!(public(ip), "{} should be public", ip);
+        }
+    }
+
+    #[test]
+    fn urls_are_checked_by_address_and_scheme() {
+        assert!(public_url("https://example.com/video"));
+        assert!(!public_url("http://127.0.0.1:8080/admin"));
+        assert!(!public_url("http://169.254.169.254/latest/meta-data/"));
+        assert!(!public_url("http://[::1]/"));
+        assert!(!public_url("file:///etc/passwd"));
+    }
+
+    #[tokio::test]
+    async fn local_names_do_not_resolve() {
+        let name: Name = "localhost".parse().unwrap();
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
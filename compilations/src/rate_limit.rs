@@ -7,23 +7,48 @@
 //
 // CREATED:         06/16/2022
 //
-// LAST EDITED:     06/16/2022
+// LAST EDITED:     10/19/2026
 ////
 
-use reqwest_middleware::{Error, RequestBuilder};
+use reqwest::Request;
+use reqwest_middleware::{ClientWithMiddleware, Error, RequestBuilder};
 use tokio::{
     sync::{mpsc, oneshot}, task::JoinError, time::{sleep, Duration},
 };
+use tracing::{event, Level};
+use crate::retry::{
+    self, CircuitBreaker, CircuitOpen, MAX_BACKOFF, MAX_RETRIES,
+};
+
+// Minimum time between two requests to Reddit.
+pub const REQUEST_INTERVAL: Duration = Duration::from_secs(2);
 
 type ResponseResult = Result<reqwest::Response, Error>;
+type Job = (ClientWithMiddleware, Request, oneshot::Sender<ResponseResult>);
+
+// Number of interactive requests that may be sent back-to-back while
+// background requests are waiting.
+const MAX_INTERACTIVE_BURST: u32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Priority {
+    // Someone is waiting on the response, e.g. the next page of posts.
+    Interactive,
+
+    // Work that can tolerate delay, e.g. unsaving posts.
+    Background,
+}
 
 #[derive(Clone)]
 pub struct RateLimiter {
-    tx: mpsc::Sender<(RequestBuilder, oneshot::Sender<ResponseResult>)>,
+    interactive_tx: mpsc::Sender<Job>,
+    background_tx: mpsc::Sender<Job>,
+    breaker: CircuitBreaker,
 }
 
 pub struct ResponderTask {
-    rx: mpsc::Receiver<(RequestBuilder, oneshot::Sender<ResponseResult>)>,
+    interactive_rx: mpsc::Receiver<Job>,
+    background_rx: mpsc::Receiver<Job>,
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -31,12 +56,51 @@ pub struct ResponderTask {
 ////
 
 impl ResponderTask {
+    // Interactive requests go first, but after MAX_INTERACTIVE_BURST of them
+    // in a row a waiting background request gets a turn, so it can't starve.
+    async fn next_job(&mut self, burst: &mut u32) -> Option<Job> {
+        if *burst >= MAX_INTERACTIVE_BURST {
+            if let Ok(job) = self.background_rx.try_recv() {
+                *burst = 0;
+                return Some(job);
+            }
+        }
+
+        tokio::select! {
+            biased;
+            Some(job) = self.interactive_rx.recv() => {
+                *burst += 1;
+                Some(job)
+            },
+            Some(job) = self.background_rx.recv() => {
+                *burst = 0;
+                Some(job)
+            },
+            else => None,
+        }
+    }
+
     pub async fn spawn(mut self) -> Result<(), JoinError> {
         let task = tokio::spawn(async move {
-            while let Some((request, channel)) = self.rx.recv().await {
-                let delay = sleep(Duration::from_secs(2));
-                let response = request.send().await;
-                channel.send(response).unwrap();
+            let mut burst = 0;
+            while let Some((client, request, mut channel)) =
+                self.next_job(&mut burst).await
+            {
+                // The client went away while this request was queued, so
+                // don't spend any of the budget on it.
+                if channel.is_closed() {
+                    continue;
+                }
+
+                // If the client disconnects while we're waiting on Reddit,
+                // drop the in-flight request to cancel it.
+                let delay = sleep(REQUEST_INTERVAL);
+                tokio::select! {
+                    response = client.execute(request) => {
+                        let _ = channel.send(response);
+                    },
+                    _ = channel.closed() => {},
+                }
                 delay.await;
             }
         });
@@ -51,20 +115,82 @@ impl ResponderTask {
 
 impl RateLimiter {
     pub fn new() -> (Self, ResponderTask) {
-        let (tx, rx) = mpsc::channel(32);
-        let rate_limiter = Self {tx};
-        let responder = ResponderTask {rx};
+        let (interactive_tx, interactive_rx) = mpsc::channel(32);
+        let (background_tx, background_rx) = mpsc::channel(32);
+        let breaker = CircuitBreaker::new();
+        let rate_limiter = Self {interactive_tx, background_tx, breaker};
+        let responder = ResponderTask {interactive_rx, background_rx};
 
         (rate_limiter, responder)
     }
 
-    pub async fn send(&mut self, request: RequestBuilder) -> ResponseResult {
+    // Wait for a turn to send the request, and send it.
+    async fn enqueue(
+        &mut self, client: ClientWithMiddleware, request: Request,
+        priority: Priority,
+    ) -> ResponseResult
+    {
         let (response_tx, response_rx) = oneshot::channel();
-        if let Err(_) = self.tx.send((request, response_tx)).await {
+        let tx = match priority {
+            Priority::Interactive => &self.interactive_tx,
+            Priority::Background => &self.background_tx,
+        };
+        if let Err(_) = tx.send((client, request, response_tx)).await {
             panic!("Rate Limiting channel receiver dropped!");
         }
         response_rx.await.unwrap()
     }
+
+    // Send the request when it's its turn. Idempotent requests that fail
+    // transiently are retried after a backoff, which is waited out before
+    // they're queued again, so nobody else's requests are held up by it.
+    pub async fn send(
+        &mut self, client: &ClientWithMiddleware, request: RequestBuilder,
+        priority: Priority,
+    ) -> ResponseResult
+    {
+        let request = request.build()?;
+        let retryable = retry::is_idempotent(request.method());
+        let mut attempt = 0;
+        let mut request = Some(request);
+        let mut result = None;
+        loop {
+            // Fail fast instead of waiting in the queue during an outage.
+            if !self.breaker.admit() {
+                return result.unwrap_or(Err(Error::middleware(CircuitOpen)));
+            }
+
+            // Keep a copy of the request in case this attempt fails.
+            let current = request.take().unwrap();
+            request = match retryable {
+                true => current.try_clone(),
+                false => None,
+            };
+
+            let response = self.enqueue(client.clone(), current, priority)
+                .await;
+            if !retry::is_transient(&response) {
+                self.breaker.record_success();
+                return response;
+            }
+
+            self.breaker.record_failure();
+            if request.is_none() || attempt >= MAX_RETRIES {
+                return response;
+            }
+            let delay = match retry::retry_after(&response) {
+                // Not worth keeping the client waiting for.
+                Some(delay) if delay > MAX_BACKOFF => return response,
+                Some(delay) => delay,
+                None => retry::backoff(attempt),
+            };
+            event!(Level::WARN, "Retrying request in {:?} (attempt {})",
+                   delay, attempt + 1);
+            result = Some(response);
+            sleep(delay).await;
+            attempt += 1;
+        }
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
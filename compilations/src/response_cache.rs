@@ -0,0 +1,195 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            response_cache.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Coalesces identical Reddit GETs and caches the responses.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicU64, Ordering}, Arc, Mutex,
+};
+
+use axum::{body::Bytes, http::{HeaderMap, StatusCode}};
+use tokio::{sync::broadcast, time::{Duration, Instant}};
+
+// A fully buffered upstream response.
+#[derive(Clone)]
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+type FetchResult = Result<CachedResponse, StatusCode>;
+
+// Entries are keyed by (user, url). The user is an opaque identifier, not
+// their token.
+type CacheKey = (String, String);
+
+enum Entry {
+    // A request for this key is in flight. Waiters subscribe to the result.
+    // The number tells the request apart from ones started for the same key
+    // after it was invalidated.
+    Pending(u64, broadcast::Sender<FetchResult>),
+
+    // A successful response, valid until the Instant.
+    Ready(Instant, CachedResponse),
+}
+
+#[derive(Clone)]
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<CacheKey, Entry>>>,
+    next_request: Arc<AtomicU64>,
+}
+
+// Removes a Pending entry if the request that created it never finishes, e.g.
+// because the client that started it disconnected. Dropping the sender wakes
+// the waiters, who then retry.
+struct PendingGuard<'a> {
+    cache: &'a ResponseCache,
+    key: Option<CacheKey>,
+    request: u64,
+}
+
+enum Role {
+    Hit(CachedResponse),
+    Leader(u64, broadcast::Sender<FetchResult>),
+    Waiter(broadcast::Receiver<FetchResult>),
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// PendingGuard
+////
+
+impl<'a> PendingGuard<'a> {
+    fn disarm(mut self) {
+        self.key = None;
+    }
+}
+
+impl<'a> Drop for PendingGuard<'a> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            let mut entries = self.cache.entries.lock().unwrap();
+            if self.cache.is_pending(&entries, &key, self.request) {
+                entries.remove(&key);
+            }
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// ResponseCache
+////
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            next_request: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    // A zero TTL turns both coalescing and caching off.
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    // Forget everything cached for the user, e.g. after they've modified
+    // their saved list. Requests that are still in flight are forgotten too:
+    // their responses may predate the change, so they're handed to the
+    // callers already waiting for them, but not stored.
+    pub fn invalidate_user(&self, user: &str) {
+        self.entries.lock().unwrap()
+            .retain(|(entry_user, _), _| entry_user != user);
+    }
+
+    // Whether the key's entry is still the one made for the request.
+    fn is_pending(
+        &self, entries: &HashMap<CacheKey, Entry>, key: &CacheKey,
+        request: u64,
+    ) -> bool
+    {
+        matches!(entries.get(key), Some(Entry::Pending(pending, _))
+                 if *pending == request)
+    }
+
+    // Return the cached response for (user, url), or run fetch to get one.
+    // Concurrent calls for the same key share a single invocation of fetch.
+    pub async fn get_or_fetch<F, Fut>(
+        &self, user: &str, url: &str, fetch: F,
+    ) -> FetchResult
+    where F: FnOnce() -> Fut,
+          Fut: Future<Output = FetchResult>,
+    {
+        let key = (user.to_string(), url.to_string());
+        loop {
+            match self.claim(&key) {
+                Role::Hit(response) => return Ok(response),
+                Role::Waiter(mut receiver) => match receiver.recv().await {
+                    Ok(result) => return result,
+                    // The leader was dropped before it finished. Try again.
+                    Err(_) => continue,
+                },
+                Role::Leader(request, sender) => {
+                    let guard = PendingGuard {
+                        cache: self, key: Some(key.clone()), request,
+                    };
+                    let result = fetch().await;
+                    let mut entries = self.entries.lock().unwrap();
+                    if self.is_pending(&entries, &key, request) {
+                        match &result {
+                            Ok(response) if response.status.is_success() => {
+                                let expires = Instant::now() + self.ttl;
+                                let response = response.clone();
+                                entries.insert(
+                                    key, Entry::Ready(expires, response));
+                            },
+                            _ => { entries.remove(&key); },
+                        }
+                    }
+                    drop(entries);
+                    guard.disarm();
+                    let _ = sender.send(result.clone());
+                    return result;
+                },
+            }
+        }
+    }
+
+    // Look up the key. On a miss, the caller either joins an in-flight request
+    // or becomes responsible for making one.
+    fn claim(&self, key: &CacheKey) -> Role {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        entries.retain(|_, entry| match entry {
+            Entry::Ready(expires, _) => *expires > now,
+            Entry::Pending(..) => true,
+        });
+
+        match entries.get(key) {
+            Some(Entry::Ready(_, response)) => Role::Hit(response.clone()),
+            Some(Entry::Pending(_, sender)) =>
+                Role::Waiter(sender.subscribe()),
+            None => {
+                let request = self.next_request
+                    .fetch_add(1, Ordering::Relaxed);
+                let (sender, _) = broadcast::channel(1);
+                entries.insert(
+                    key.clone(), Entry::Pending(request, sender.clone()));
+                Role::Leader(request, sender)
+            },
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
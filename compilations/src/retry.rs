@@ -0,0 +1,164 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            retry.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Retry policy and circuit breaker for Reddit requests.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use rand::Rng;
+use reqwest::{Method, Response, StatusCode, header::RETRY_AFTER};
+use reqwest_middleware::{Error, Result};
+use tokio::time::{Duration, Instant};
+use tracing::{event, Level};
+use crate::rate_limit::REQUEST_INTERVAL;
+
+// Number of times a failed idempotent request is retried.
+pub const MAX_RETRIES: u32 = 3;
+
+// Consecutive failures after which the circuit opens.
+const FAILURE_THRESHOLD: u32 = 5;
+
+// How long the circuit stays open before a trial request is let through.
+const OPEN_DURATION: Duration = Duration::from_secs(30);
+
+// Upper bound on the delay between two attempts.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// Returned instead of sending a request while the circuit is open.
+#[derive(Debug)]
+pub struct CircuitOpen;
+
+struct BreakerState {
+    failures: u32,
+    open_until: Option<Instant>,
+
+    // When the trial request of a half-open circuit was let through. If it
+    // never reports back, e.g. because its client went away, another one is
+    // let through after OPEN_DURATION.
+    trial_since: Option<Instant>,
+}
+
+// Shared record of recent upstream failures. Once FAILURE_THRESHOLD requests
+// have failed in a row, requests fail immediately for OPEN_DURATION.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    state: Arc<Mutex<BreakerState>>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// CircuitOpen
+////
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Reddit is unavailable, not sending request")
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+///////////////////////////////////////////////////////////////////////////////
+// CircuitBreaker
+////
+
+impl CircuitBreaker {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(BreakerState {
+                failures: 0,
+                open_until: None,
+                trial_since: None,
+            })),
+        }
+    }
+
+    // Whether a request may be sent now. Once OPEN_DURATION has passed, the
+    // circuit is half-open: a single trial request is let through, and the
+    // circuit closes if it succeeds or opens again if it fails.
+    pub fn admit(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match state.open_until {
+            None => true,
+            Some(open_until) if open_until > now => false,
+            Some(_) => match state.trial_since {
+                Some(since) if since + OPEN_DURATION > now => false,
+                _ => {
+                    state.trial_since = Some(now);
+                    true
+                },
+            },
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.failures = 0;
+        state.open_until = None;
+        state.trial_since = None;
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.failures += 1;
+        if state.trial_since.take().is_some() {
+            event!(Level::WARN, "Trial request failed, reopening circuit");
+            state.open_until = Some(now + OPEN_DURATION);
+        } else if state.failures >= FAILURE_THRESHOLD
+            && state.open_until.is_none()
+        {
+            event!(Level::WARN, "Opening circuit after {} failures",
+                   state.failures);
+            state.open_until = Some(now + OPEN_DURATION);
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Retries
+////
+
+// Whether the outcome of a request suggests that trying again may succeed.
+pub fn is_transient(result: &Result<Response>) -> bool {
+    match result {
+        Ok(response) => response.status().is_server_error()
+            || response.status() == StatusCode::TOO_MANY_REQUESTS,
+        Err(Error::Reqwest(e)) => e.is_connect() || e.is_timeout(),
+        Err(Error::Middleware(_)) => false,
+    }
+}
+
+pub fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS
+             | Method::PUT | Method::DELETE)
+}
+
+// Honor a Retry-After (in seconds) if the server sent one.
+pub fn retry_after(result: &Result<Response>) -> Option<Duration> {
+    let response = result.as_ref().ok()?;
+    let seconds = response.headers().get(RETRY_AFTER)?.to_str().ok()?
+        .parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+// Exponential backoff with full jitter. The retry is queued in the
+// RateLimiter again afterwards, so it's never sent sooner than
+// REQUEST_INTERVAL after the last request either.
+pub fn backoff(attempt: u32) -> Duration {
+    let ceiling = REQUEST_INTERVAL.saturating_mul(1 << attempt.min(16))
+        .min(MAX_BACKOFF);
+    let spread = ceiling.saturating_sub(REQUEST_INTERVAL).as_millis();
+    let jitter = rand::thread_rng().gen_range(0..=spread) as u64;
+    REQUEST_INTERVAL + Duration::from_millis(jitter)
+}
+
+///////////////////////////////////////////////////////////////////////////////
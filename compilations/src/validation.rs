@@ -0,0 +1,238 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            validation.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Checks that extracted media can actually be played before
+//                  it's handed to the client.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::fmt;
+
+use model::{MediaDescriptor, MediaKind, MediaSource};
+use reqwest::{header, Client, StatusCode};
+use tokio::time::Duration;
+use tracing::{event, Level};
+use crate::configuration::MediaValidationConfiguration;
+use crate::extractors::ExtractError;
+use crate::public_address;
+use crate::USER_AGENT;
+
+// How long to wait for a media host to answer.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Types that browsers won't play, even if a host serves them.
+const UNPLAYABLE_TYPES: &[&'static str] = &[
+    "video/x-flv", "video/x-ms-wmv", "video/x-msvideo", "video/avi",
+];
+
+// Why a source was rejected.
+#[derive(Debug)]
+enum Rejection {
+    Unreachable(String),
+    Status(StatusCode),
+
+    // e.g. an HTML error page served with a 200
+    NotMedia(String),
+    Unplayable(String),
+    Empty,
+    TooLarge(u64),
+}
+
+pub struct Validator {
+    client: Client,
+
+    // Bytes
+    max_size: Option<u64>,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Unreachable(e) => write!(f, "unreachable ({})", e),
+            Rejection::Status(status) => write!(f, "status {}", status),
+            Rejection::NotMedia(content_type) =>
+                write!(f, "not media ({})", content_type),
+            Rejection::Unplayable(content_type) =>
+                write!(f, "unplayable type {}", content_type),
+            Rejection::Empty => write!(f, "empty"),
+            Rejection::TooLarge(size) =>
+                write!(f, "too large ({} bytes)", size),
+        }
+    }
+}
+
+// The total size of the resource, from either response to a HEAD or a
+// partial GET ("Content-Range: bytes 0-0/12345").
+fn resource_size(response: &reqwest::Response) -> Option<u64> {
+    let headers = response.headers();
+    let from_range = headers.get(header::CONTENT_RANGE)
+        .and_then(|range| range.to_str().ok())
+        .and_then(|range| range.rsplit_once('/'))
+        .and_then(|(_, total)| total.parse().ok());
+    from_range.or_else(|| {
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => None,
+            _ => headers.get(header::CONTENT_LENGTH)
+                .and_then(|length| length.to_str().ok())
+                .and_then(|length| length.parse().ok()),
+        }
+    })
+}
+
+impl Validator {
+    pub fn new(configuration: &MediaValidationConfiguration) ->
+        reqwest::Result<Self>
+    {
+        // The sources came from pages that came from users, so they're kept
+        // to public addresses like the extractors' requests are.
+        let client = public_address::client_builder()
+            .user_agent(USER_AGENT)
+            .timeout(TIMEOUT)
+            .build()?;
+        Ok(Self { client, max_size: configuration.max_size })
+    }
+
+    // Ask the host about the source without downloading it. Not every host
+    // answers HEAD requests, so fall back to asking for the first byte.
+    async fn probe(&self, source: &MediaSource) ->
+        Result<reqwest::Response, Rejection>
+    {
+        let unreachable = |e: reqwest::Error| {
+            Rejection::Unreachable(e.to_string())
+        };
+        let url = reqwest::Url::parse(&source.url)
+            .map_err(|e| Rejection::Unreachable(e.to_string()))?;
+        if !public_address::is_public_url(&url) {
+            let error = public_address::NotPublic(source.url.clone());
+            return Err(Rejection::Unreachable(error.to_string()));
+        }
+        let response = self.client.head(&source.url).send().await
+            .map_err(unreachable)?;
+        match response.status() {
+            StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+                | StatusCode::FORBIDDEN => {},
+            _ if response.headers().contains_key(header::CONTENT_TYPE) =>
+                return Ok(response),
+            _ => {},
+        }
+
+        self.client.get(&source.url)
+            .header(header::RANGE, "bytes=0-0")
+            .send().await
+            .map_err(unreachable)
+    }
+
+    async fn check(&self, source: &MediaSource) -> Result<(), Rejection> {
+        let response = self.probe(source).await?;
+        if !response.status().is_success() {
+            return Err(Rejection::Status(response.status()));
+        }
+
+        let content_type = response.headers().get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .map(|content_type| {
+                content_type.split(';').next().unwrap_or("").trim()
+                    .to_lowercase()
+            })
+            .unwrap_or_default();
+        let is_media = content_type.starts_with("video/")
+            || content_type.starts_with("audio/")
+            || content_type.starts_with("image/")
+            // Some hosts don't bother labelling their files.
+            || content_type == "application/octet-stream"
+            || content_type.contains("mpegurl")
+            || content_type.contains("dash+xml");
+        if !is_media {
+            return Err(Rejection::NotMedia(content_type));
+        }
+        if UNPLAYABLE_TYPES.contains(&content_type.as_str()) {
+            return Err(Rejection::Unplayable(content_type));
+        }
+
+        match (resource_size(&response), self.max_size) {
+            (Some(0), _) => Err(Rejection::Empty),
+            (Some(size), Some(max_size)) if size > max_size =>
+                Err(Rejection::TooLarge(size)),
+            _ => Ok(()),
+        }
+    }
+
+    // Drop sources from the front of the list until one is playable. Those
+    // behind it are left as they are: the browser only gets to them if the
+    // first one fails anyway.
+    async fn validate_sources(&self, sources: &mut Vec<MediaSource>) ->
+        Result<(), ExtractError>
+    {
+        let mut reasons = Vec::new();
+        while let Some(source) = sources.first() {
+            match self.check(source).await {
+                Ok(()) => return Ok(()),
+                Err(rejection) => {
+                    event!(Level::DEBUG, "Rejected {}: {}", source.url,
+                           rejection);
+                    reasons.push(rejection.to_string());
+                    sources.remove(0);
+                },
+            }
+        }
+
+        Err(ExtractError::Unplayable(match reasons.is_empty() {
+            true => "no sources".to_string(),
+            false => reasons.join(", "),
+        }))
+    }
+
+    // Validate media other than a gallery.
+    async fn validate_single(&self, media: &mut MediaDescriptor) ->
+        Result<(), ExtractError>
+    {
+        // Embeds are web pages, and are expected to look like them.
+        if media.kind == MediaKind::Embed {
+            return Ok(());
+        }
+
+        self.validate_sources(&mut media.sources).await?;
+
+        // Silent video is better than no video.
+        if let Some(audio) = &media.audio {
+            if let Err(rejection) = self.check(audio).await {
+                event!(Level::WARN, "Dropping audio {}: {}", audio.url,
+                       rejection);
+                media.audio = None;
+            }
+        }
+        Ok(())
+    }
+
+    // Make sure the first choice of everything in the descriptor is
+    // playable, falling back to other renditions where it isn't. Gallery
+    // items that can't be played are dropped.
+    pub async fn validate(&self, media: &mut MediaDescriptor) ->
+        Result<(), ExtractError>
+    {
+        if media.kind != MediaKind::Gallery {
+            return self.validate_single(media).await;
+        }
+
+        let mut items = Vec::new();
+        let mut reasons = Vec::new();
+        for mut item in media.items.drain(..) {
+            match self.validate_single(&mut item).await {
+                Ok(()) => items.push(item),
+                Err(e) => reasons.push(e.to_string()),
+            }
+        }
+        media.items = items;
+        match media.items.is_empty() {
+            true => Err(ExtractError::Unplayable(reasons.join("; "))),
+            false => Ok(()),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
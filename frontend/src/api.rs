@@ -7,10 +7,17 @@
 //
 // CREATED:         06/16/2022
 //
-// LAST EDITED:     06/19/2022
+// LAST EDITED:     10/19/2026
 ////
 
-use model::MediaUrlRequest;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+use model::{
+    FilterRules, IdentityRequest, ListingSource, MediaDescriptor,
+    MediaIdentity, MediaResult, MediaUrlRequest, OrderedRequest,
+    PostMediaRequest, QualityPreference,
+};
 use js_sys::{Array, Reflect};
 use wasm_bindgen_futures::JsFuture;
 use wasm_bindgen::{JsCast, JsValue};
@@ -19,16 +26,78 @@ use wasm_bindgen::{JsCast, JsValue};
 // application from the build configuration.
 const PUBLIC_URL: &'static str = "/compilations";
 
-async fn fetch(request: web_sys::Request) -> Result<JsValue, JsValue> {
-    let window = web_sys::window().unwrap();
-    let value = JsFuture::from(window.fetch_with_request(&request)).await?;
+// The most prefetched results kept waiting for get_media. Posts that never
+// play (they're skipped, or the list is replaced) never ask for theirs, so
+// past this the oldest results are dropped.
+const MAX_PREFETCHED: usize = 32;
 
-    // Convert the response body to JSON.
+thread_local! {
+    // Results of prefetch, oldest first, until get_media asks for them.
+    static PREFETCHED: RefCell<VecDeque<MediaResult>> =
+        RefCell::new(VecDeque::new());
+
+    // Sent along with every get_media request.
+    static QUALITY: RefCell<QualityPreference> =
+        RefCell::new(QualityPreference::default());
+
+    // Copies of posts that are unsaved along with the post, by the post's
+    // fullname.
+    static COPIES: RefCell<HashMap<String, Copies>> =
+        RefCell::new(HashMap::new());
+}
+
+#[derive(Default)]
+struct Copies {
+    names: Vec<String>,
+
+    // Whether the original has been unsaved already
+    unsaved: bool,
+}
+
+// Send the request. If the session has expired, or predates something the
+// backend needs to know about it, the user is sent to log in again.
+async fn send(request: &web_sys::Request) ->
+    Result<web_sys::Response, JsValue>
+{
+    let window = web_sys::window().unwrap();
+    let value = JsFuture::from(window.fetch_with_request(request)).await?;
     assert!(value.is_instance_of::<web_sys::Response>());
     let response: web_sys::Response = value.dyn_into()?;
+    if response.status() == 401 {
+        window.location()
+            .set_href(&(PUBLIC_URL.to_string() + "/login"))?;
+        return Err("not logged in".into());
+    }
+    Ok(response)
+}
+
+async fn fetch(request: web_sys::Request) -> Result<JsValue, JsValue> {
+    let response = send(&request).await?;
+
+    // Convert the response body to JSON.
     Ok(JsFuture::from(response.json().unwrap()).await?)
 }
 
+// Query string for the /video endpoint, e.g. "?max_height=720".
+fn quality_query(preference: &QualityPreference) -> String {
+    let mut params = Vec::new();
+    if let Some(max_height) = preference.max_height {
+        params.push("max_height=".to_string() + &max_height.to_string());
+    }
+    if let Some(max_bandwidth) = preference.max_bandwidth {
+        params.push("max_bandwidth=".to_string()
+                    + &max_bandwidth.to_string());
+    }
+    if preference.prefer_audio {
+        params.push("prefer_audio=true".to_string());
+    }
+
+    match params.is_empty() {
+        true => String::new(),
+        false => "?".to_string() + &params.join("&"),
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 // Public API
 ////
@@ -40,37 +109,51 @@ pub async fn get_identity() -> Result<JsValue, JsValue> {
     fetch(request).await
 }
 
-// Iterate through a collection of saved posts
+// Iterate through a listing of posts, e.g. the user's saved posts
 #[derive(Clone)]
 pub struct PostCollection {
-    username: String,
+    source: ListingSource,
     after: Option<String>,
     count: u32,
+
+    // Whether the last page has been fetched
+    done: bool,
 }
 
 impl PostCollection {
-    pub fn new(username: &str) -> Self {
-        let username = username.split('/').last().unwrap().to_owned();
+    pub fn new(source: ListingSource) -> Self {
         Self {
-            username,
+            source,
             after: None,
             count: 0,
+            done: false,
         }
     }
 
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
     pub async fn next(&mut self) -> Result<Array, JsValue> {
-        if self.after.is_none() && self.count != 0 {
+        if self.done {
             return Ok(Array::new());
         }
 
         // Kick off request to get more, adding query params if this isn't our
         // first request.
-        let mut url = PUBLIC_URL.to_string() + "/user/" + &self.username
-            + "/saved";
+        let mut params = self.source.query().into_iter()
+            .map(|(name, value)| {
+                name.to_string() + "=" + &String::from(
+                    js_sys::encode_uri_component(&value))
+            })
+            .collect::<Vec<_>>();
         if let Some(after) = &self.after {
-            let query = "?after=".to_owned() + after + "&count="
-                + &self.count.to_string();
-            url += &query;
+            params.push("after=".to_owned() + after);
+            params.push("count=".to_owned() + &self.count.to_string());
+        }
+        let mut url = PUBLIC_URL.to_string() + &self.source.path();
+        if !params.is_empty() {
+            url += &("?".to_string() + &params.join("&"));
         }
 
         // Get response
@@ -84,6 +167,7 @@ impl PostCollection {
         let children = Array::from(&Reflect::get(&data, &"children".into())?);
         if after.is_null() {
             self.after = None;
+            self.done = true;
         } else {
             self.after = Some(after.as_string().unwrap());
             self.count = self.count + children.length();
@@ -93,35 +177,188 @@ impl PostCollection {
     }
 }
 
-pub async fn get_video(request: MediaUrlRequest) -> Result<String, JsValue> {
+// Iterate through a compilation that the backend has put in order. The
+// backend fetches every post of it when the first page is asked for.
+#[derive(Clone)]
+pub struct OrderedCollection {
+    request: OrderedRequest,
+
+    // Names the ordered compilation, once the backend has made it
+    cursor: Option<String>,
+    offset: u32,
+    done: bool,
+
+    // What the backend shuffled with, so the order can be had again
+    seed: Option<u32>,
+}
+
+impl OrderedCollection {
+    pub fn new(request: OrderedRequest) -> Self {
+        Self { request, cursor: None, offset: 0, done: false, seed: None }
+    }
+
+    pub fn seed(&self) -> Option<u32> {
+        self.seed
+    }
+
+    pub async fn next(&mut self) -> Result<Array, JsValue> {
+        if self.done {
+            return Ok(Array::new());
+        }
+
+        let page = match &self.cursor {
+            None => {
+                let url = PUBLIC_URL.to_string() + "/ordered";
+                let page = send_json("POST", &url, &self.request).await?;
+                self.seed = Reflect::get(&page, &"seed".into())?.as_f64()
+                    .map(|seed| seed as u32);
+                page
+            },
+            Some(cursor) => {
+                let url = PUBLIC_URL.to_string() + "/ordered/" + cursor
+                    + "?offset=" + &self.offset.to_string();
+                fetch(web_sys::Request::new_with_str(&url)?).await?
+            },
+        };
+
+        self.cursor = Reflect::get(&page, &"cursor".into())?.as_string();
+        match Reflect::get(&page, &"next".into())?.as_f64() {
+            Some(next) => self.offset = next as u32,
+            None => self.done = true,
+        }
+        Ok(Array::from(&Reflect::get(&page, &"posts".into())?))
+    }
+}
+
+// Send the body as JSON with the method, returning the JSON response, or
+// null for a 204. Errors come back as a plain text reason.
+async fn send_json<T: serde::Serialize>(
+    method: &str, request_url: &str, body: &T,
+) -> Result<JsValue, JsValue>
+{
     // Send the request as JSON body
     let headers = web_sys::Headers::new()?;
     headers.set("Content-Type", "application/json").unwrap();
 
     let mut request_init = web_sys::RequestInit::new();
     request_init.headers(&headers.into());
-    request_init.method("POST");
-    let body = serde_json::to_string(&request)
+    request_init.method(method);
+    let body = serde_json::to_string(body)
         .map_err(|e| JsValue::from(e.to_string()))?;
     request_init.body(Some(&body.into()));
-
-    let request_url = PUBLIC_URL.to_string() + "/video";
     let request = web_sys::Request::new_with_str_and_init(
-        &request_url, &request_init)?;
+        request_url, &request_init)?;
 
     // Send request
-    let window = web_sys::window().unwrap();
-    let value = JsFuture::from(window.fetch_with_request(&request)).await?;
+    let response = send(&request).await?;
+    if !response.ok() {
+        return Err(JsFuture::from(response.text()?).await?);
+    }
+    if response.status() == 204 {
+        return Ok(JsValue::NULL);
+    }
+    JsFuture::from(response.json()?).await
+}
 
-    // Convert the response body to text.
-    assert!(value.is_instance_of::<web_sys::Response>());
-    let response: web_sys::Response = value.dyn_into()?;
-    JsFuture::from(response.text().unwrap()).await?
-        .into_serde::<String>()
-        .map_err(|e| e.to_string().into())
+// POST the body to one of the /video endpoints, with the quality preference
+// in the query string.
+async fn post_video<T: serde::Serialize>(path: &str, body: &T) ->
+    Result<JsValue, JsValue>
+{
+    let query = QUALITY.with(|quality| quality_query(&quality.borrow()));
+    let url = PUBLIC_URL.to_string() + path + &query;
+    send_json("POST", &url, body).await
 }
 
-pub async fn unsave(id: &str) -> Result<(), JsValue> {
+// Resolve the media behind a post.
+pub async fn get_media(request: PostMediaRequest) ->
+    Result<MediaDescriptor, JsValue>
+{
+    let prefetched = PREFETCHED.with(|prefetched| {
+        let mut prefetched = prefetched.borrow_mut();
+        let index = prefetched.iter()
+            .position(|result| result.url == request.request.url)?;
+        prefetched.remove(index)
+    });
+    if let Some(result) = prefetched {
+        return match (result.media, result.error) {
+            (Some(media), _) => Ok(media),
+            (None, error) => Err(error.unwrap_or_default().into()),
+        };
+    }
+
+    post_video("/video", &request).await?
+        .into_serde()
+        .map_err(|e| JsValue::from(e.to_string()))
+}
+
+// The URL of the preferred source of the media behind a post, for callers
+// that can only play a single URL.
+pub async fn get_video(request: MediaUrlRequest) -> Result<String, JsValue> {
+    let media = get_media(request.into()).await?;
+    media.primary()
+        .map(|source| source.url.clone())
+        .ok_or_else(|| JsValue::from("no media sources"))
+}
+
+// Resolve the media behind several posts ahead of time. get_media answers
+// from the results, rather than asking the backend again.
+pub async fn prefetch(requests: Vec<PostMediaRequest>) ->
+    Result<(), JsValue>
+{
+    if requests.is_empty() {
+        return Ok(());
+    }
+
+    let results: Vec<MediaResult> = post_video("/video/batch", &requests)
+        .await?
+        .into_serde()
+        .map_err(|e| JsValue::from(e.to_string()))?;
+    PREFETCHED.with(|prefetched| {
+        let mut prefetched = prefetched.borrow_mut();
+        for result in results {
+            prefetched.retain(|existing| existing.url != result.url);
+            prefetched.push_back(result);
+        }
+        while prefetched.len() > MAX_PREFETCHED {
+            prefetched.pop_front();
+        }
+    });
+    Ok(())
+}
+
+// Set the quality constraints for subsequent get_media requests.
+pub fn set_quality_preference(preference: QualityPreference) {
+    QUALITY.with(|quality| *quality.borrow_mut() = preference);
+}
+
+// Identify the media of the posts, to tell which are of the same clip.
+pub async fn identify(requests: &[IdentityRequest]) ->
+    Result<Vec<MediaIdentity>, JsValue>
+{
+    let url = PUBLIC_URL.to_string() + "/duplicates";
+    send_json("POST", &url, &requests).await?
+        .into_serde()
+        .map_err(|e| JsValue::from(e.to_string()))
+}
+
+// The user's filter rules, as they were last saved.
+pub async fn get_filters() -> Result<FilterRules, JsValue> {
+    let endpoint = PUBLIC_URL.to_string() + "/filters";
+    let request = web_sys::Request::new_with_str(&endpoint)?;
+    fetch(request).await?
+        .into_serde()
+        .map_err(|e| JsValue::from(e.to_string()))
+}
+
+// Save the user's filter rules, so they're kept across sessions. Rules the
+// backend can't evaluate, such as invalid patterns, are rejected.
+pub async fn save_filters(rules: &FilterRules) -> Result<(), JsValue> {
+    let url = PUBLIC_URL.to_string() + "/filters";
+    send_json("PUT", &url, rules).await.map(|_| ())
+}
+
+async fn unsave_one(id: &str) -> Result<(), JsValue> {
     let mut request_init = web_sys::RequestInit::new();
     request_init.method("POST");
     let request_url = PUBLIC_URL.to_string() + "/api/unsave?id=" + id;
@@ -129,15 +366,44 @@ pub async fn unsave(id: &str) -> Result<(), JsValue> {
         &request_url, &request_init)?;
 
     // Send request
-    let window = web_sys::window().unwrap();
-    let value = JsFuture::from(window.fetch_with_request(&request)).await?;
-
-    assert!(value.is_instance_of::<web_sys::Response>());
-    let response: web_sys::Response = value.dyn_into()?;
+    let response = send(&request).await?;
     match response.ok() {
         true => Ok(()),
         false => Err(response.status_text().into()),
     }
 }
 
+// Unsave the post, and any copies of it that have been found.
+pub async fn unsave(id: &str) -> Result<(), JsValue> {
+    unsave_one(id).await?;
+    let copies = COPIES.with(|copies| {
+        let mut copies = copies.borrow_mut();
+        let copies = copies.entry(id.to_string()).or_default();
+        copies.unsaved = true;
+        std::mem::take(&mut copies.names)
+    });
+    for copy in copies {
+        if let Err(e) = unsave_one(&copy).await {
+            web_sys::console::error_2(&e, &"while unsaving a copy".into());
+        }
+    }
+    Ok(())
+}
+
+// Unsave the copy once the original is unsaved, or now, if it already is.
+pub async fn unsave_copy(original: &str, copy: &str) -> Result<(), JsValue> {
+    let unsaved = COPIES.with(|copies| {
+        let mut copies = copies.borrow_mut();
+        let copies = copies.entry(original.to_string()).or_default();
+        if !copies.unsaved {
+            copies.names.push(copy.to_string());
+        }
+        copies.unsaved
+    });
+    match unsaved {
+        true => unsave_one(copy).await,
+        false => Ok(()),
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,154 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            comment_card.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     A component showing a saved comment for a while.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use gloo_timers::callback::Timeout;
+use model::Comment;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use web_sys::Element;
+use yew::prelude::*;
+
+const REDDIT_URL: &'static str = "https://www.reddit.com";
+
+#[derive(PartialEq, Properties)]
+pub struct CommentCardProperties {
+    pub comment: Comment,
+
+    // Seconds the comment is shown for
+    pub duration: u32,
+    pub onended: Callback<()>,
+}
+
+pub enum CommentCardMessage {
+    Ended,
+}
+
+pub struct CommentCard {
+    body: NodeRef,
+
+    // Dropping it cancels the timer.
+    timer: Option<Timeout>,
+}
+
+// Links in comments may be relative to Reddit, e.g. /r/videos. Anything
+// other than a web link (say, javascript:) is dropped.
+fn safe_url(url: CowStr) -> CowStr {
+    let lowercase = url.trim().to_lowercase();
+    if lowercase.starts_with("https://") || lowercase.starts_with("http://") {
+        url
+    } else if url.starts_with('/') && !url.starts_with("//") {
+        (REDDIT_URL.to_string() + &url).into()
+    } else {
+        "".into()
+    }
+}
+
+// Render the body of a comment. Listings escape &, < and >, unless they're
+// requested with raw_json. HTML in the comment is shown as written, rather
+// than interpreted.
+fn render_markdown(markdown: &str) -> String {
+    let markdown = markdown.replace("&lt;", "<").replace("&gt;", ">")
+        .replace("&amp;", "&");
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    let events = Parser::new_ext(&markdown, options).map(|event| {
+        match event {
+            Event::Html(html) => Event::Text(html),
+            Event::Start(Tag::Link(kind, url, title)) =>
+                Event::Start(Tag::Link(kind, safe_url(url), title)),
+            Event::Start(Tag::Image(kind, url, title)) =>
+                Event::Start(Tag::Image(kind, safe_url(url), title)),
+            event => event,
+        }
+    });
+
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+    output
+}
+
+impl CommentCard {
+    fn start_timer(&mut self, context: &Context<Self>) {
+        let link = context.link().clone();
+        let milliseconds = context.props().duration.saturating_mul(1000);
+        self.timer = Some(Timeout::new(milliseconds, move || {
+            link.send_message(CommentCardMessage::Ended);
+        }));
+    }
+}
+
+impl Component for CommentCard {
+    type Message = CommentCardMessage;
+    type Properties = CommentCardProperties;
+
+    fn create(context: &Context<Self>) -> Self {
+        let mut card = Self { body: NodeRef::default(), timer: None };
+        card.start_timer(context);
+        card
+    }
+
+    fn changed(&mut self, context: &Context<Self>,
+               _old_props: &Self::Properties) -> bool
+    {
+        self.start_timer(context);
+        true
+    }
+
+    fn update(&mut self, context: &Context<Self>, message: Self::Message) ->
+        bool
+    {
+        match message {
+            CommentCardMessage::Ended => {
+                self.timer = None;
+                context.props().onended.emit(());
+                false
+            },
+        }
+    }
+
+    // The body is rendered outside of yew, since it's HTML already.
+    fn rendered(&mut self, context: &Context<Self>, _first_render: bool) {
+        if let Some(body) = self.body.cast::<Element>() {
+            let comment = &context.props().comment;
+            body.set_inner_html(&render_markdown(&comment.body));
+        }
+    }
+
+    fn view(&self, context: &Context<Self>) -> Html {
+        let comment = &context.props().comment;
+        html! {
+            <>
+                <div class="media comment-card">
+                    <p class="text comment-author">{
+                        format!("u/{} · {} points", comment.author,
+                                comment.score)
+                    }</p>
+                    <div class="text comment-body" ref={self.body.clone()} />
+                    if let Some(permalink) = &comment.link_permalink {
+                        <a class="text" href={permalink.clone()}
+                         target="_blank" rel="noopener noreferrer">{
+                            format!("On \"{}\"", comment.link_title)
+                        }</a>
+                    }
+                </div>
+                <button class="next-button"
+                 onclick={context.link().callback(|_| {
+                     CommentCardMessage::Ended
+                 })}>
+                    {"Next"}
+                </button>
+            </>
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
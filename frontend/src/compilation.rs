@@ -0,0 +1,259 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            compilation.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Posts drawn from several listings at once.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::collections::{HashSet, VecDeque};
+
+use js_sys::{Array, Reflect};
+use model::{ListingSource, OrderedRequest, PostOrder};
+use wasm_bindgen::JsValue;
+use crate::api::{OrderedCollection, PostCollection};
+use crate::duplicates::Deduplicator;
+
+// Most posts handed out by one call to Compilation::next.
+const BATCH_SIZE: usize = 25;
+
+// How the posts of the sources are merged.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Interleaving {
+    // Take from each source in turn, as often as its weight says.
+    #[default]
+    RoundRobin,
+
+    // Take from a source chosen at random, in proportion to its weight.
+    Random,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedSource {
+    pub source: ListingSource,
+
+    // Relative share of the compilation
+    pub weight: u32,
+}
+
+// One source of the compilation, with the posts fetched from it that haven't
+// been handed out yet.
+#[derive(Clone)]
+struct Stream {
+    collection: PostCollection,
+    weight: u32,
+    buffer: VecDeque<JsValue>,
+
+    // For smooth weighted round-robin
+    current: i64,
+
+    // Whether a page of the source failed to load, e.g. because it's a
+    // private or misspelled subreddit. It's left out from then on.
+    failed: bool,
+}
+
+// Pages through several listings, handing out their posts interleaved.
+// Posts that are in more than one of the listings are only handed out once.
+// In any order but Reddit's, the backend merges the listings instead, and
+// the weights and interleaving don't apply.
+#[derive(Clone)]
+pub struct Compilation {
+    streams: Vec<Stream>,
+    interleaving: Interleaving,
+    ordered: Option<OrderedCollection>,
+
+    // Fullnames of the posts handed out so far
+    seen: HashSet<String>,
+
+    // Drops posts of clips that were handed out already, if it's wanted
+    duplicates: Option<Deduplicator>,
+}
+
+impl Stream {
+    fn is_done(&self) -> bool {
+        self.failed || self.collection.is_done()
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.buffer.is_empty() && self.is_done()
+    }
+
+    fn needs_page(&self) -> bool {
+        self.buffer.is_empty() && !self.is_done()
+    }
+}
+
+impl Compilation {
+    pub fn new(sources: Vec<WeightedSource>, interleaving: Interleaving,
+               order: PostOrder, seed: Option<u32>) -> Self
+    {
+        let sources = sources.into_iter()
+            .filter(|source| source.weight > 0)
+            .collect::<Vec<_>>();
+        if order != PostOrder::Listing {
+            let request = OrderedRequest {
+                sources: sources.into_iter()
+                    .map(|source| source.source)
+                    .collect(),
+                order, seed,
+            };
+            return Self {
+                streams: Vec::new(),
+                interleaving,
+                ordered: Some(OrderedCollection::new(request)),
+                seen: HashSet::new(),
+                duplicates: None,
+            };
+        }
+
+        let streams = sources.into_iter()
+            .map(|source| Stream {
+                collection: PostCollection::new(source.source),
+                weight: source.weight,
+                buffer: VecDeque::new(),
+                current: 0,
+                failed: false,
+            })
+            .collect();
+        Self {
+            streams, interleaving, ordered: None, seen: HashSet::new(),
+            duplicates: None,
+        }
+    }
+
+    // The seed a shuffled compilation was shuffled with, once the backend
+    // has shuffled it.
+    pub fn seed(&self) -> Option<u32> {
+        self.ordered.as_ref().and_then(OrderedCollection::seed)
+    }
+
+    // Hand out each clip once, however many times it was posted. Copies
+    // may be unsaved along with the post that's played.
+    pub fn without_duplicates(mut self, unsave_copies: bool) -> Self {
+        self.duplicates = Some(Deduplicator::new(unsave_copies));
+        self
+    }
+
+    // Fetch the next page of every source that has run out of posts. A
+    // source that fails doesn't stop the others, unless they've all failed.
+    async fn refill(&mut self) -> Result<(), JsValue> {
+        let mut error = None;
+        for stream in self.streams.iter_mut()
+            .filter(|stream| stream.needs_page())
+        {
+            match stream.collection.next().await {
+                Ok(page) => stream.buffer.extend(page.iter()),
+                Err(e) => {
+                    web_sys::console::error_2(
+                        &e, &"while fetching a source's posts".into());
+                    stream.failed = true;
+                    error = Some(e);
+                },
+            }
+        }
+        match (error, self.streams.iter().all(|stream| stream.failed)) {
+            (Some(e), true) => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    // Pick the stream to take the next post from, among those with posts
+    // left to take. Smooth weighted round-robin spreads each source's turns
+    // out, rather than taking them all at once.
+    fn pick(&mut self) -> Option<usize> {
+        let candidates = self.streams.iter().enumerate()
+            .filter(|(_, stream)| !stream.buffer.is_empty())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let total = candidates.iter()
+            .map(|index| i64::from(self.streams[*index].weight))
+            .sum::<i64>();
+        if total == 0 {
+            return None;
+        }
+
+        match self.interleaving {
+            Interleaving::RoundRobin => {
+                for index in &candidates {
+                    let stream = &mut self.streams[*index];
+                    stream.current += i64::from(stream.weight);
+                }
+                let chosen = *candidates.iter()
+                    .max_by_key(|index| self.streams[**index].current)?;
+                self.streams[chosen].current -= total;
+                Some(chosen)
+            },
+            Interleaving::Random => {
+                let mut target = (js_sys::Math::random() * total as f64)
+                    as i64;
+                for index in &candidates {
+                    target -= i64::from(self.streams[*index].weight);
+                    if target < 0 {
+                        return Some(*index);
+                    }
+                }
+                candidates.last().copied()
+            },
+        }
+    }
+
+    // The next batch of posts, like PostCollection::next. The batch is only
+    // empty once every source is exhausted.
+    pub async fn next(&mut self) -> Result<Array, JsValue> {
+        loop {
+            let batch = match self.ordered.as_mut() {
+                Some(ordered) => ordered.next().await?,
+                None => self.interleave().await?,
+            };
+            let batch = match self.duplicates.as_mut() {
+                Some(duplicates) if batch.length() > 0 =>
+                    duplicates.filter(batch).await,
+                _ => return Ok(batch),
+            };
+
+            // A batch of nothing but copies isn't the end of the posts.
+            if batch.length() > 0 {
+                return Ok(batch);
+            }
+        }
+    }
+
+    // The next batch of posts of the sources, interleaved.
+    async fn interleave(&mut self) -> Result<Array, JsValue> {
+        let batch = Array::new();
+        while batch.length() == 0 {
+            self.refill().await?;
+            if self.streams.iter().all(|stream| stream.is_exhausted()) {
+                break;
+            }
+
+            // Stop as soon as any source needs another page, so that the
+            // sources keep their shares of the compilation.
+            while (batch.length() as usize) < BATCH_SIZE
+                && !self.streams.iter().any(Stream::needs_page)
+            {
+                let index = match self.pick() {
+                    Some(index) => index,
+                    None => break,
+                };
+                let value = self.streams[index].buffer.pop_front().unwrap();
+                let name = Reflect::get(&value, &"data".into()).ok()
+                    .and_then(|data| Reflect::get(&data, &"name".into()).ok())
+                    .and_then(|name| name.as_string());
+                let duplicate = name
+                    .map(|name| !self.seen.insert(name))
+                    .unwrap_or(false);
+                if !duplicate {
+                    batch.push(&value);
+                }
+            }
+        }
+        Ok(batch)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,93 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            duplicates.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Drops posts of clips that have been handed out already.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use js_sys::Array;
+use model::MediaIdentity;
+use wasm_bindgen_futures::spawn_local;
+use crate::api::{identify, unsave_copy};
+use crate::post::identity_request;
+
+#[derive(Clone, Default)]
+pub struct Deduplicator {
+    // Identities of the clips handed out so far
+    clips: Vec<MediaIdentity>,
+
+    // Whether copies are unsaved along with the post that was played
+    unsave_copies: bool,
+}
+
+impl Deduplicator {
+    pub fn new(unsave_copies: bool) -> Self {
+        Self { clips: Vec::new(), unsave_copies }
+    }
+
+    // The posts of the batch whose clips haven't been seen before. If the
+    // backend can't identify them, they're all let through.
+    pub async fn filter(&mut self, batch: Array) -> Array {
+        // Comments aren't identified, and are always let through.
+        let requests = batch.iter()
+            .map(|value| identity_request(&value))
+            .collect::<Vec<_>>();
+        let identified = requests.iter().flatten().cloned()
+            .collect::<Vec<_>>();
+        if identified.is_empty() {
+            return batch;
+        }
+        let identities = match identify(&identified).await {
+            Ok(identities) => identities,
+            Err(e) => {
+                web_sys::console::error_2(
+                    &e, &"while identifying posts".into());
+                return batch;
+            },
+        };
+
+        let mut identities = identities.into_iter();
+        batch.iter().zip(requests)
+            .filter(|(_, request)| match request {
+                Some(_) => identities.next()
+                    .map(|identity| self.is_new(identity))
+                    .unwrap_or(true),
+                None => true,
+            })
+            .map(|(value, _)| value)
+            .collect()
+    }
+
+    // Remember the clip if it's new. Otherwise, it's a copy of one that was
+    // handed out before.
+    fn is_new(&mut self, identity: MediaIdentity) -> bool {
+        let original = self.clips.iter()
+            .find(|clip| identity.is_copy_of(clip))
+            .map(|clip| clip.name.clone());
+        if original.is_none()
+            && !self.clips.iter().any(|clip| identity.is_duplicate_of(clip))
+        {
+            self.clips.push(identity);
+            return true;
+        }
+
+        // Thumbnails that look alike are reason enough to skip a post, but
+        // not to unsave it.
+        if let (true, Some(original)) = (self.unsave_copies, original) {
+            spawn_local(async move {
+                if let Err(e) = unsave_copy(&original, &identity.name).await {
+                    web_sys::console::error_2(
+                        &e, &"while unsaving a copy".into());
+                }
+            });
+        }
+        false
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
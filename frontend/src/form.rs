@@ -7,15 +7,24 @@
 //
 // CREATED:         06/13/2022
 //
-// LAST EDITED:     06/20/2022
+// LAST EDITED:     10/19/2026
 ////
 
+use model::{
+    Filter, FilterRules, FlagRule, ListingSort, ListingSource, PostOrder,
+    QualityPreference, SensitiveMode, TimeRange, MAX_SOURCES,
+};
 use wasm_bindgen_futures::spawn_local;
-use web_sys::HtmlInputElement;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
-use crate::api::get_identity;
+use crate::api::{get_filters, get_identity, save_filters};
+use crate::compilation::{Interleaving, WeightedSource};
 use crate::filter::IdentityFilter;
-use crate::view::ApplicationData;
+use crate::post::CommentMode;
+use crate::view::{
+    ApplicationData, DEFAULT_COMMENT_DURATION, DEFAULT_GIF_MIN_DURATION,
+    DEFAULT_GIF_MIN_LOOPS, DEFAULT_SLIDE_DURATION,
+};
 
 #[derive(Clone, Default, PartialEq, Properties)]
 pub struct AppFormModel {
@@ -24,13 +33,378 @@ pub struct AppFormModel {
 
 pub enum AppFormMessage {
     Identity(String),
+
+    // The filter rules the user saved last time.
+    Filters(FilterRules),
+
+    // Add the listing that's currently described to the compilation.
+    AddSource,
+    RemoveSource(usize),
     Start,
 }
 
 #[derive(Default)]
 pub struct AppForm {
     username: Option<String>,
+
+    // Listings added to the compilation so far
+    sources: Vec<WeightedSource>,
+    source: NodeRef,
+    listing_name: NodeRef,
+    search_subreddit: NodeRef,
+    sort: NodeRef,
+    time: NodeRef,
+    weight: NodeRef,
+    interleaving: NodeRef,
+    order: NodeRef,
+    seed: NodeRef,
+    order_error: Option<String>,
     debug: NodeRef,
+    max_height: NodeRef,
+    max_bitrate: NodeRef,
+    prefer_audio: NodeRef,
+    slide_duration: NodeRef,
+    gif_min_duration: NodeRef,
+    gif_min_loops: NodeRef,
+    comments: NodeRef,
+    comment_duration: NodeRef,
+    unsave_comments: NodeRef,
+    skip_duplicates: NodeRef,
+    unsave_duplicates: NodeRef,
+
+    // Filter rules, as they were loaded
+    filter: FilterRules,
+    filter_error: Option<String>,
+    include_subreddits: NodeRef,
+    exclude_subreddits: NodeRef,
+    include_domains: NodeRef,
+    exclude_domains: NodeRef,
+    include_flair: NodeRef,
+    exclude_flair: NodeRef,
+    nsfw: NodeRef,
+    spoilers: NodeRef,
+    sensitive: NodeRef,
+    min_score: NodeRef,
+    min_duration: NodeRef,
+    max_duration: NodeRef,
+    title_pattern: NodeRef,
+    exclude_title_pattern: NodeRef,
+    max_age: NodeRef,
+}
+
+// The number in a numeric input, or None if it was left blank.
+fn input_number<T: std::str::FromStr>(input: &NodeRef) -> Option<T> {
+    input.cast::<HtmlInputElement>().unwrap().value().trim().parse().ok()
+}
+
+fn select_value(select: &NodeRef) -> String {
+    select.cast::<HtmlSelectElement>().unwrap().value()
+}
+
+// The text in an input, or None if it was left blank.
+fn input_text(input: &NodeRef) -> Option<String> {
+    let value = input.cast::<HtmlInputElement>().unwrap().value();
+    match value.trim() {
+        "" => None,
+        value => Some(value.to_string()),
+    }
+}
+
+// The comma-separated items in an input.
+fn input_list(input: &NodeRef) -> Vec<String> {
+    input_text(input).unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn flag_rule(select: &NodeRef) -> FlagRule {
+    match select_value(select).as_str() {
+        "exclude" => FlagRule::Exclude,
+        "only" => FlagRule::Only,
+        _ => FlagRule::Allow,
+    }
+}
+
+fn optional_value<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+fn view_list_input(id: &'static str, label: &'static str, input: &NodeRef,
+                   items: &[String]) -> Html
+{
+    html! {
+        <div class="input-group">
+            <input {id} type="text" name={id} placeholder="Comma-separated"
+             value={items.join(", ")} ref={input.clone()} />
+            <label class="text" for={id}>{ label }</label>
+        </div>
+    }
+}
+
+fn view_flag_select(id: &'static str, label: &'static str, select: &NodeRef,
+                    rule: FlagRule) -> Html
+{
+    html! {
+        <div class="input-group">
+            <select {id} name={id} ref={select.clone()}>
+                <option value="allow" selected={rule == FlagRule::Allow}>{
+                    "Allow"
+                }</option>
+                <option value="exclude" selected={rule == FlagRule::Exclude}>{
+                    "Exclude"
+                }</option>
+                <option value="only" selected={rule == FlagRule::Only}>{
+                    "Only"
+                }</option>
+            </select>
+            <label class="text" for={id}>{ label }</label>
+        </div>
+    }
+}
+
+// Short description of a listing, e.g. "/r/videos/top?t=week".
+fn describe_source(source: &ListingSource) -> String {
+    let query = source.query().into_iter()
+        .map(|(name, value)| name.to_string() + "=" + &value)
+        .collect::<Vec<_>>();
+    match query.is_empty() {
+        true => source.path(),
+        false => source.path() + "?" + &query.join("&"),
+    }
+}
+
+impl AppForm {
+    fn weighted_source(&self) -> WeightedSource {
+        WeightedSource {
+            source: self.source(),
+            weight: input_number(&self.weight)
+                .filter(|weight| *weight > 0)
+                .unwrap_or(1),
+        }
+    }
+
+    // The listings to play from. If none have been added, it's the one
+    // that's currently described.
+    fn sources(&self) -> Vec<WeightedSource> {
+        match self.sources.is_empty() {
+            true => vec![self.weighted_source()],
+            false => self.sources.clone(),
+        }
+    }
+
+    fn interleaving(&self) -> Interleaving {
+        match select_value(&self.interleaving).as_str() {
+            "random" => Interleaving::Random,
+            _ => Interleaving::RoundRobin,
+        }
+    }
+
+    fn order(&self) -> PostOrder {
+        match select_value(&self.order).as_str() {
+            "reversed" => PostOrder::Reversed,
+            "shuffle" => PostOrder::Shuffle,
+            "score" => PostOrder::Score,
+            "subreddit" => PostOrder::Subreddit,
+            "duration" => PostOrder::Duration,
+            _ => PostOrder::Listing,
+        }
+    }
+
+    // The listing to play posts from. Listings of a user default to the
+    // logged in user, and multireddits may be given as "someone/m/name" to
+    // use someone else's.
+    fn source(&self) -> ListingSource {
+        let username = self.username.clone().unwrap_or_default();
+        let name = input_text(&self.listing_name);
+        let sort = match select_value(&self.sort).as_str() {
+            "new" => ListingSort::New,
+            "top" => ListingSort::Top,
+            "rising" => ListingSort::Rising,
+            "relevance" => ListingSort::Relevance,
+            _ => ListingSort::Hot,
+        };
+        let time = match select_value(&self.time).as_str() {
+            "hour" => TimeRange::Hour,
+            "day" => TimeRange::Day,
+            "month" => TimeRange::Month,
+            "year" => TimeRange::Year,
+            "all" => TimeRange::All,
+            _ => TimeRange::Week,
+        };
+
+        match (select_value(&self.source).as_str(), name) {
+            ("upvoted", _) => ListingSource::Upvoted { username },
+            ("subreddit", Some(subreddit)) =>
+                ListingSource::Subreddit { subreddit, sort, time },
+            ("multireddit", Some(name)) => {
+                let (username, name) = match name.split_once("/m/") {
+                    Some((owner, name)) =>
+                        (owner.to_string(), name.to_string()),
+                    None => (username, name),
+                };
+                ListingSource::Multireddit { username, name, sort, time }
+            },
+            ("submitted", name) => ListingSource::Submitted {
+                username: name.unwrap_or(username), sort, time,
+            },
+            ("search", Some(query)) => ListingSource::Search {
+                query, subreddit: input_text(&self.search_subreddit), sort,
+                time,
+            },
+            _ => ListingSource::Saved { username },
+        }
+    }
+
+    fn comment_mode(&self) -> CommentMode {
+        match select_value(&self.comments).as_str() {
+            "card" => CommentMode::Card,
+            "parent" => CommentMode::ParentMedia,
+            _ => CommentMode::Skip,
+        }
+    }
+
+    fn filter_rules(&self) -> FilterRules {
+        FilterRules {
+            include_subreddits: input_list(&self.include_subreddits),
+            exclude_subreddits: input_list(&self.exclude_subreddits),
+            include_domains: input_list(&self.include_domains),
+            exclude_domains: input_list(&self.exclude_domains),
+            include_flair: input_list(&self.include_flair),
+            exclude_flair: input_list(&self.exclude_flair),
+            nsfw: flag_rule(&self.nsfw),
+            spoilers: flag_rule(&self.spoilers),
+            sensitive: match select_value(&self.sensitive).as_str() {
+                "blur" => SensitiveMode::Blur,
+                "skip" => SensitiveMode::Skip,
+                _ => SensitiveMode::Show,
+            },
+            min_score: input_number(&self.min_score),
+            min_duration: input_number(&self.min_duration),
+            max_duration: input_number(&self.max_duration),
+            title_pattern: input_text(&self.title_pattern),
+            exclude_title_pattern: input_text(&self.exclude_title_pattern),
+            max_age: input_number(&self.max_age),
+        }
+    }
+
+    fn view_filters(&self) -> Html {
+        let rules = &self.filter;
+        html! {
+            <>
+                <p class="text">{ "Filters" }</p>
+                { view_list_input("include-subreddits", "Only Subreddits",
+                                  &self.include_subreddits,
+                                  &rules.include_subreddits) }
+                { view_list_input("exclude-subreddits", "Exclude Subreddits",
+                                  &self.exclude_subreddits,
+                                  &rules.exclude_subreddits) }
+                { view_list_input("include-domains", "Only Domains",
+                                  &self.include_domains,
+                                  &rules.include_domains) }
+                { view_list_input("exclude-domains", "Exclude Domains",
+                                  &self.exclude_domains,
+                                  &rules.exclude_domains) }
+                { view_list_input("include-flair", "Only Flair",
+                                  &self.include_flair, &rules.include_flair) }
+                { view_list_input("exclude-flair", "Exclude Flair",
+                                  &self.exclude_flair, &rules.exclude_flair) }
+                { view_flag_select("nsfw", "NSFW Posts", &self.nsfw,
+                                   rules.nsfw) }
+                { view_flag_select("spoilers", "Spoilers", &self.spoilers,
+                                   rules.spoilers) }
+                <div class="input-group">
+                    <select id="sensitive" name="sensitive"
+                     ref={self.sensitive.clone()}>
+                        <option value="show"
+                         selected={rules.sensitive == SensitiveMode::Show}>{
+                            "Show"
+                        }</option>
+                        <option value="blur"
+                         selected={rules.sensitive == SensitiveMode::Blur}>{
+                            "Blur Until Clicked"
+                        }</option>
+                        <option value="skip"
+                         selected={rules.sensitive == SensitiveMode::Skip}>{
+                            "Skip"
+                        }</option>
+                    </select>
+                    <label class="text" for="sensitive">{
+                        "NSFW Posts And Spoilers"
+                    }</label>
+                </div>
+                <div class="input-group">
+                    <input id="min-score" type="number" name="min-score"
+                     placeholder="Any" value={optional_value(&rules.min_score)}
+                     ref={self.min_score.clone()} />
+                    <label class="text" for="min-score">{
+                        "Minimum Score"
+                    }</label>
+                </div>
+                <div class="input-group">
+                    <input id="min-duration" type="number" min="0"
+                     name="min-duration" placeholder="Any"
+                     value={optional_value(&rules.min_duration)}
+                     ref={self.min_duration.clone()} />
+                    <label class="text" for="min-duration">{
+                        "Minimum Video Length (seconds)"
+                    }</label>
+                </div>
+                <div class="input-group">
+                    <input id="max-duration" type="number" min="0"
+                     name="max-duration" placeholder="Any"
+                     value={optional_value(&rules.max_duration)}
+                     ref={self.max_duration.clone()} />
+                    <label class="text" for="max-duration">{
+                        "Maximum Video Length (seconds)"
+                    }</label>
+                </div>
+                <div class="input-group">
+                    <input id="title-pattern" type="text"
+                     name="title-pattern" placeholder="Regular expression"
+                     value={optional_value(&rules.title_pattern)}
+                     ref={self.title_pattern.clone()} />
+                    <label class="text" for="title-pattern">{
+                        "Title Matches"
+                    }</label>
+                </div>
+                <div class="input-group">
+                    <input id="exclude-title-pattern" type="text"
+                     name="exclude-title-pattern"
+                     placeholder="Regular expression"
+                     value={optional_value(&rules.exclude_title_pattern)}
+                     ref={self.exclude_title_pattern.clone()} />
+                    <label class="text" for="exclude-title-pattern">{
+                        "Title Doesn't Match"
+                    }</label>
+                </div>
+                <div class="input-group">
+                    <input id="max-age" type="number" min="0" name="max-age"
+                     placeholder="Any" value={optional_value(&rules.max_age)}
+                     ref={self.max_age.clone()} />
+                    <label class="text" for="max-age">{
+                        "Maximum Age (days)"
+                    }</label>
+                </div>
+                if let Some(error) = &self.filter_error {
+                    <p class="text">{ error }</p>
+                }
+            </>
+        }
+    }
+
+    fn quality(&self) -> QualityPreference {
+        QualityPreference {
+            max_height: input_number(&self.max_height),
+            // The form asks for kbit/s
+            max_bandwidth: input_number::<u64>(&self.max_bitrate)
+                .map(|kbps| kbps * 1000),
+            prefer_audio: self.prefer_audio.cast::<HtmlInputElement>()
+                .unwrap().checked(),
+        }
+    }
 }
 
 impl Component for AppForm {
@@ -46,6 +420,16 @@ impl Component for AppForm {
             link.emit(username.unwrap());
         });
 
+        // Users that have never saved any filters get the defaults.
+        let link = context.link().callback(|rules| Filters(rules));
+        spawn_local(async move {
+            match get_filters().await {
+                Ok(rules) => link.emit(rules),
+                Err(e) => web_sys::console::error_2(
+                    &e, &"while loading filters".into()),
+            }
+        });
+
         Self::default()
     }
 
@@ -54,14 +438,82 @@ impl Component for AppForm {
     {
         match message {
             AppFormMessage::Start => {
+                // Catch invalid patterns before the user gets going.
+                let filter = self.filter_rules();
+                if let Err(e) = Filter::new(filter.clone()) {
+                    self.filter_error = Some(e.to_string());
+                    return true;
+                }
+                // The backend merges the sources in any order but Reddit's,
+                // so there's nothing to weigh or interleave them by.
+                let sources = self.sources();
+                let weighted = sources.iter().any(|source| source.weight != 1);
+                if self.order() != PostOrder::Listing && (weighted
+                    || self.interleaving() != Interleaving::RoundRobin)
+                {
+                    self.order_error = Some(
+                        "Weights and mixing only apply in Reddit's order"
+                            .to_string());
+                    return true;
+                }
+                self.order_error = None;
+
+                if filter != self.filter {
+                    let rules = filter.clone();
+                    spawn_local(async move {
+                        if let Err(e) = save_filters(&rules).await {
+                            web_sys::console::error_2(
+                                &e, &"while saving filters".into());
+                        }
+                    });
+                }
+
                 let data = ApplicationData {
-                    username: self.username.as_ref().unwrap().clone(),
+                    sources,
+                    interleaving: self.interleaving(),
+                    order: self.order(),
+                    seed: input_number(&self.seed),
                     debug: self.debug.cast::<HtmlInputElement>().unwrap()
                         .checked(),
+                    quality: self.quality(),
+                    slide_duration: input_number(&self.slide_duration)
+                        .filter(|seconds| *seconds > 0)
+                        .unwrap_or(DEFAULT_SLIDE_DURATION),
+                    gif_min_duration: input_number(&self.gif_min_duration)
+                        .unwrap_or(DEFAULT_GIF_MIN_DURATION),
+                    gif_min_loops: input_number(&self.gif_min_loops)
+                        .filter(|loops| *loops > 0)
+                        .unwrap_or(DEFAULT_GIF_MIN_LOOPS),
+                    comments: self.comment_mode(),
+                    comment_duration: input_number(&self.comment_duration)
+                        .filter(|seconds| *seconds > 0)
+                        .unwrap_or(DEFAULT_COMMENT_DURATION),
+                    unsave_comments: self.unsave_comments
+                        .cast::<HtmlInputElement>().unwrap().checked(),
+                    filter,
+                    skip_duplicates: self.skip_duplicates
+                        .cast::<HtmlInputElement>().unwrap().checked(),
+                    unsave_duplicates: self.unsave_duplicates
+                        .cast::<HtmlInputElement>().unwrap().checked(),
                 };
                 context.props().callback.emit(data);
                 false
             },
+            AppFormMessage::AddSource => {
+                if self.sources.len() >= MAX_SOURCES {
+                    return false;
+                }
+                self.sources.push(self.weighted_source());
+                true
+            },
+            AppFormMessage::RemoveSource(index) => {
+                self.sources.remove(index);
+                true
+            },
+            AppFormMessage::Filters(rules) => {
+                self.filter = rules;
+                true
+            },
             AppFormMessage::Identity(data) => {
                 self.username = Some(data.clone());
                 web_sys::console::log_1(&data.into());
@@ -82,6 +534,240 @@ impl Component for AppForm {
                          value="yes" ref={self.debug.clone()} />
                         <label class="text" for="debug">{"Debug Mode"}</label>
                     </div>
+                    <div class="input-group">
+                        <select id="source" name="source"
+                         ref={self.source.clone()}>
+                            <option value="saved" selected=true>{
+                                "Saved"
+                            }</option>
+                            <option value="upvoted">{"Upvoted"}</option>
+                            <option value="subreddit">{"Subreddit"}</option>
+                            <option value="multireddit">{
+                                "Multireddit"
+                            }</option>
+                            <option value="submitted">{
+                                "User's Submissions"
+                            }</option>
+                            <option value="search">{"Search"}</option>
+                        </select>
+                        <label class="text" for="source">{"Play From"}</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="listing-name" type="text"
+                         name="listing-name"
+                         placeholder="Subreddit, multireddit, user or query"
+                         ref={self.listing_name.clone()} />
+                        <label class="text" for="listing-name">{
+                            "Listing"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="search-subreddit" type="text"
+                         name="search-subreddit" placeholder="Anywhere"
+                         ref={self.search_subreddit.clone()} />
+                        <label class="text" for="search-subreddit">{
+                            "Search In Subreddit"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <select id="sort" name="sort"
+                         ref={self.sort.clone()}>
+                            <option value="hot" selected=true>{"Hot"}</option>
+                            <option value="new">{"New"}</option>
+                            <option value="top">{"Top"}</option>
+                            <option value="rising">{"Rising"}</option>
+                            <option value="relevance">{
+                                "Relevance (search only)"
+                            }</option>
+                        </select>
+                        <label class="text" for="sort">{"Sort By"}</label>
+                    </div>
+                    <div class="input-group">
+                        <select id="time" name="time"
+                         ref={self.time.clone()}>
+                            <option value="hour">{"Past Hour"}</option>
+                            <option value="day">{"Past Day"}</option>
+                            <option value="week" selected=true>{
+                                "Past Week"
+                            }</option>
+                            <option value="month">{"Past Month"}</option>
+                            <option value="year">{"Past Year"}</option>
+                            <option value="all">{"All Time"}</option>
+                        </select>
+                        <label class="text" for="time">{
+                            "From (top and search only)"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="weight" type="number" min="1"
+                         name="weight" placeholder="1"
+                         ref={self.weight.clone()} />
+                        <label class="text" for="weight">{"Weight"}</label>
+                        <button disabled={self.sources.len() >= MAX_SOURCES}
+                         onclick={context.link().callback(
+                            |e: MouseEvent| {
+                                e.prevent_default();
+                                AppFormMessage::AddSource
+                            })}>{ "Add Source" }</button>
+                    </div>
+                    <ul class="text">
+                        { for self.sources.iter().enumerate().map(
+                            |(index, source)| html! {
+                                <li>
+                                    { format!("{} (weight {}) ",
+                                              describe_source(&source.source),
+                                              source.weight) }
+                                    <button onclick={context.link().callback(
+                                        move |e: MouseEvent| {
+                                            e.prevent_default();
+                                            AppFormMessage::RemoveSource(index)
+                                        })}>{ "Remove" }</button>
+                                </li>
+                            }) }
+                    </ul>
+                    <div class="input-group">
+                        <select id="interleaving" name="interleaving"
+                         ref={self.interleaving.clone()}>
+                            <option value="round-robin" selected=true>{
+                                "Take Turns"
+                            }</option>
+                            <option value="random">{"Random"}</option>
+                        </select>
+                        <label class="text" for="interleaving">{
+                            "Mix Sources By"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <select id="order" name="order"
+                         ref={self.order.clone()}>
+                            <option value="listing" selected=true>{
+                                "Reddit's Order"
+                            }</option>
+                            <option value="reversed">{
+                                "Reversed (oldest first)"
+                            }</option>
+                            <option value="shuffle">{"Shuffled"}</option>
+                            <option value="score">{"Highest Score"}</option>
+                            <option value="subreddit">{"Subreddit"}</option>
+                            <option value="duration">{
+                                "Shortest Video"
+                            }</option>
+                        </select>
+                        <label class="text" for="order">{
+                            "Play In Order"
+                        }</label>
+                    </div>
+                    if let Some(error) = &self.order_error {
+                        <p class="text">{ error }</p>
+                    }
+                    <div class="input-group">
+                        <input id="seed" type="number" min="0"
+                         max={u32::MAX.to_string()} name="seed"
+                         placeholder="Random" ref={self.seed.clone()} />
+                        <label class="text" for="seed">{
+                            "Shuffle Seed"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="max-height" type="number" min="0"
+                         name="max-height" placeholder="Any"
+                         ref={self.max_height.clone()} />
+                        <label class="text" for="max-height">{
+                            "Maximum Resolution (lines)"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="max-bitrate" type="number" min="0"
+                         name="max-bitrate" placeholder="Any"
+                         ref={self.max_bitrate.clone()} />
+                        <label class="text" for="max-bitrate">{
+                            "Maximum Bitrate (kbit/s)"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="prefer-audio" type="checkbox"
+                         name="prefer-audio" value="yes"
+                         ref={self.prefer_audio.clone()} />
+                        <label class="text" for="prefer-audio">{
+                            "Prefer Videos With Sound"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="slide-duration" type="number" min="1"
+                         name="slide-duration"
+                         placeholder={DEFAULT_SLIDE_DURATION.to_string()}
+                         ref={self.slide_duration.clone()} />
+                        <label class="text" for="slide-duration">{
+                            "Seconds Per Image"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="gif-min-duration" type="number" min="0"
+                         name="gif-min-duration"
+                         placeholder={DEFAULT_GIF_MIN_DURATION.to_string()}
+                         ref={self.gif_min_duration.clone()} />
+                        <label class="text" for="gif-min-duration">{
+                            "Minimum Seconds Per GIF"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="gif-min-loops" type="number" min="1"
+                         name="gif-min-loops"
+                         placeholder={DEFAULT_GIF_MIN_LOOPS.to_string()}
+                         ref={self.gif_min_loops.clone()} />
+                        <label class="text" for="gif-min-loops">{
+                            "Minimum Loops Per GIF"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <select id="comments" name="comments"
+                         ref={self.comments.clone()}>
+                            <option value="skip" selected=true>{
+                                "Skip"
+                            }</option>
+                            <option value="card">{"Show the comment"}</option>
+                            <option value="parent">{
+                                "Play the post's media"
+                            }</option>
+                        </select>
+                        <label class="text" for="comments">{
+                            "Saved Comments"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="comment-duration" type="number" min="1"
+                         name="comment-duration"
+                         placeholder={DEFAULT_COMMENT_DURATION.to_string()}
+                         ref={self.comment_duration.clone()} />
+                        <label class="text" for="comment-duration">{
+                            "Seconds Per Comment"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="unsave-comments" type="checkbox"
+                         name="unsave-comments" value="yes"
+                         ref={self.unsave_comments.clone()} />
+                        <label class="text" for="unsave-comments">{
+                            "Unsave Comments Too"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="skip-duplicates" type="checkbox"
+                         name="skip-duplicates" value="yes" checked=true
+                         ref={self.skip_duplicates.clone()} />
+                        <label class="text" for="skip-duplicates">{
+                            "Play Each Clip Once"
+                        }</label>
+                    </div>
+                    <div class="input-group">
+                        <input id="unsave-duplicates" type="checkbox"
+                         name="unsave-duplicates" value="yes"
+                         ref={self.unsave_duplicates.clone()} />
+                        <label class="text" for="unsave-duplicates">{
+                            "Unsave Copies Of Played Clips"
+                        }</label>
+                    </div>
+                    { self.view_filters() }
                     <button onclick={context.link().callback(|e: MouseEvent| {
                         e.prevent_default();
                         AppFormMessage::Start
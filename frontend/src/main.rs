@@ -7,14 +7,19 @@
 //
 // CREATED:         06/13/2022
 //
-// LAST EDITED:     06/16/2022
+// LAST EDITED:     10/19/2026
 ////
 
 use yew::prelude::*;
 
 mod api;
+mod comment_card;
+mod compilation;
+mod duplicates;
 mod filter;
 mod form;
+mod post;
+mod slideshow;
 mod video_box;
 mod view;
 
@@ -0,0 +1,424 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            post.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Saved posts, as they're queued for the player.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use js_sys::{Array, Object, Reflect};
+use model::{
+    Comment, IdentityRequest, MediaDescriptor, MediaUrlRequest, PostFacts,
+    PostMediaRequest, MAX_CROSSPOST_DEPTH,
+};
+use wasm_bindgen::JsValue;
+use crate::api;
+use crate::filter::Post;
+
+// Extensions of links that go straight to an image.
+const IMAGE_EXTENSIONS: &[&'static str] = &[
+    ".jpg", ".jpeg", ".png", ".gif", ".webp",
+];
+
+// A post the filter doesn't consider a video, but which has media we can
+// show anyway: an image, or a Reddit gallery.
+#[derive(Clone, PartialEq)]
+pub struct MediaPost {
+    // Fullname, e.g. "t3_abc123"
+    name: String,
+    title: String,
+
+    // The post's entry in the listing, as Reddit sent it.
+    data: JsValue,
+}
+
+// A crosspost, whose media is on the post it was crossposted from. The
+// crosspost is the one that was saved, so it's the one that's shown and
+// unsaved.
+#[derive(Clone, PartialEq)]
+pub struct Crosspost {
+    name: String,
+    title: String,
+    original: Box<QueuedPost>,
+
+    // The crosspost's, which the backend checks the user's rules against,
+    // as they were checked here.
+    facts: Option<PostFacts>,
+}
+
+// What to do with saved comments.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CommentMode {
+    #[default]
+    Skip,
+
+    // Show the comment itself, as a card.
+    Card,
+
+    // Play the media of the post the comment was left on.
+    ParentMedia,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct SavedComment {
+    pub comment: Comment,
+
+    // The comment's entry in the listing, as Reddit sent it.
+    data: JsValue,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum QueuedPost {
+    // A video the filter recognised, and its entry in the listing
+    Video(Post, JsValue),
+    Media(MediaPost),
+    Crosspost(Crosspost),
+
+    // A comment, shown as a card
+    Comment(SavedComment),
+
+    // A comment, for which the media of its post is played
+    CommentParent(SavedComment),
+}
+
+fn get_string(object: &JsValue, key: &str) -> Option<String> {
+    Reflect::get(object, &key.into()).ok()?.as_string()
+}
+
+fn get_bool(object: &JsValue, key: &str) -> bool {
+    Reflect::get(object, &key.into()).ok()
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+fn get_f64(object: &JsValue, key: &str) -> Option<f64> {
+    Reflect::get(object, &key.into()).ok()?.as_f64()
+}
+
+fn get_path(object: &JsValue, path: &[&str]) -> Option<JsValue> {
+    path.iter().try_fold(object.clone(), |object, key| {
+        Reflect::get(&object, &(*key).into()).ok()
+            .filter(|value| !value.is_undefined() && !value.is_null())
+    })
+}
+
+// Reddit transcodes animated images to MP4, and lists the result as a
+// variant of the post's preview. Unless the listing was requested with
+// raw_json, its URLs are HTML-escaped.
+fn mp4_variant(data: &JsValue) -> Option<String> {
+    let images = Array::from(&get_path(data, &["preview", "images"])?);
+    let url = get_path(&images.get(0), &["variants", "mp4", "source", "url"])?
+        .as_string()?;
+    Some(url.replace("&amp;", "&"))
+}
+
+// The request for the media the post links to.
+fn link_request(value: &JsValue) -> Option<PostMediaRequest> {
+    let data = Reflect::get(value, &"data".into()).ok()?;
+    let is_gif = ["secure_media", "media"].iter()
+        .find_map(|key| get_path(&data, &[key, "reddit_video", "is_gif"]))
+        .and_then(|is_gif| is_gif.as_bool())
+        .unwrap_or(false);
+    let request: MediaUrlRequest = data.into_serde().ok()?;
+    Some(PostMediaRequest { request, is_gif, facts: None })
+}
+
+// The request, with what the user's rules are checked against.
+fn with_facts(request: PostMediaRequest, facts: &Option<PostFacts>) ->
+    PostMediaRequest
+{
+    PostMediaRequest { facts: facts.clone(), ..request }
+}
+
+// The request for the media behind a post, from its listing entry. The MP4
+// rendition of animated images is preferred over the GIF.
+fn media_request(value: &JsValue) -> Option<PostMediaRequest> {
+    let mut request = link_request(value)?;
+    let data = Reflect::get(value, &"data".into()).ok()?;
+    if let Some(url) = mp4_variant(&data) {
+        request.request.url = url;
+    }
+    Some(request)
+}
+
+impl MediaPost {
+    pub fn from_object(value: JsValue) -> Option<Self> {
+        if get_string(&value, "kind").as_deref() != Some("t3") {
+            return None;
+        }
+
+        let data = Reflect::get(&value, &"data".into()).ok()?;
+        let url = get_string(&data, "url")?.to_lowercase();
+        let path = url.split(|c| c == '?' || c == '#').next().unwrap_or("");
+        let is_image = get_string(&data, "post_hint").as_deref()
+            == Some("image")
+            || IMAGE_EXTENSIONS.iter().any(|extension| {
+                path.ends_with(extension)
+            });
+        if !is_image && !get_bool(&data, "is_gallery") {
+            return None;
+        }
+
+        Some(Self {
+            name: get_string(&data, "name")?,
+            title: get_string(&data, "title").unwrap_or_default(),
+            data: value,
+        })
+    }
+}
+
+impl SavedComment {
+    pub fn from_object(value: &JsValue) -> Option<Self> {
+        if get_string(value, "kind").as_deref() != Some("t1") {
+            return None;
+        }
+
+        let data = Reflect::get(value, &"data".into()).ok()?;
+        Some(Self { comment: data.into_serde().ok()?, data: value.clone() })
+    }
+
+    // The request for the media of the post the comment was left on.
+    pub fn link_request(&self) -> Option<PostMediaRequest> {
+        let link_url = self.comment.link_url.as_deref()?;
+        let data = Reflect::get(&self.data, &"data".into()).ok()?;
+        let request = Object::assign(&Object::new(), &data.into());
+        Reflect::set(&request, &"url".into(), &link_url.into()).ok()?;
+        JsValue::from(request).into_serde::<MediaUrlRequest>().ok()
+            .map(PostMediaRequest::from)
+    }
+}
+
+// The listing entry of the post a crosspost was crossposted from, and so
+// on, until we get to the original.
+fn original_entry(value: &JsValue) -> Option<JsValue> {
+    let mut data = Reflect::get(value, &"data".into()).ok()?;
+    let mut original = None;
+    for _ in 0..MAX_CROSSPOST_DEPTH {
+        match get_path(&data, &["crosspost_parent_list", "0"]) {
+            Some(parent) => data = parent,
+            None => break,
+        }
+        original = Some(data.clone());
+    }
+
+    // Parents are listed as bare post data, rather than as listing entries.
+    let original = original?;
+    let entry = Object::new();
+    Reflect::set(&entry, &"kind".into(), &"t3".into()).ok()?;
+    Reflect::set(&entry, &"data".into(), &original).ok()?;
+    Some(entry.into())
+}
+
+// The listing entry of the post whose media is played for the entry: the
+// original of a crosspost, or else the entry itself.
+fn media_entry(value: &JsValue) -> JsValue {
+    original_entry(value).unwrap_or_else(|| value.clone())
+}
+
+// What the user's filter rules are checked against, from a listing entry.
+// Crossposts are judged by where they were posted, but by the media of the
+// original. Comments are judged by the title of their post.
+pub fn post_facts(value: &JsValue) -> Option<PostFacts> {
+    let data = Reflect::get(value, &"data".into()).ok()?;
+    let media = Reflect::get(&media_entry(value), &"data".into()).ok()?;
+    let title = match get_string(value, "kind").as_deref() {
+        Some("t1") => "link_title",
+        _ => "title",
+    };
+    let duration = ["secure_media", "media"].iter()
+        .find_map(|key| get_path(&media, &[key, "reddit_video", "duration"]))
+        .and_then(|duration| duration.as_f64());
+    Some(PostFacts {
+        subreddit: get_string(&data, "subreddit"),
+        domain: get_string(&media, "domain"),
+        flair: get_string(&data, "link_flair_text"),
+        nsfw: get_bool(&data, "over_18") || get_bool(&media, "over_18"),
+        spoiler: get_bool(&data, "spoiler") || get_bool(&media, "spoiler"),
+        score: get_f64(&data, "score").map(|score| score as i64),
+        duration,
+        title: get_string(&data, title).unwrap_or_default(),
+        created: get_f64(&data, "created_utc"),
+    })
+}
+
+// The rendition of the post's preview that Reddit has already blurred, for
+// posts that are NSFW or spoilers, so the real image isn't downloaded until
+// the viewer asks to see it.
+pub fn obscured_preview(value: &JsValue) -> Option<String> {
+    let data = Reflect::get(&media_entry(value), &"data".into()).ok()?;
+    let images = Array::from(&get_path(&data, &["preview", "images"])?);
+    let url = ["obfuscated", "nsfw"].iter()
+        .find_map(|variant| get_path(
+            &images.get(0), &["variants", variant, "source", "url"]))?
+        .as_string()?;
+    Some(url.replace("&amp;", "&"))
+}
+
+// What the backend needs to identify the media of a post, from its listing
+// entry. Comments aren't identified.
+pub fn identity_request(value: &JsValue) -> Option<IdentityRequest> {
+    if get_string(value, "kind").as_deref() != Some("t3") {
+        return None;
+    }
+    let data = Reflect::get(value, &"data".into()).ok()?;
+    let original = original_entry(value)
+        .and_then(|entry| Reflect::get(&entry, &"data".into()).ok());
+    let media = original.clone().unwrap_or_else(|| data.clone());
+
+    // The smallest rendition of the preview, or else the thumbnail, which
+    // is "self", "nsfw" etc. if there isn't one.
+    let preview = get_path(&media, &["preview", "images", "0", "resolutions",
+                                     "0", "url"])
+        .and_then(|url| url.as_string())
+        .map(|url| url.replace("&amp;", "&"));
+    let thumbnail = preview.or_else(|| {
+        get_string(&media, "thumbnail")
+            .filter(|url| url.starts_with("http"))
+    });
+    Some(IdentityRequest {
+        name: get_string(&data, "name")?,
+        url: get_string(&media, "url"),
+        crosspost_parent: original.and_then(|data| get_string(&data, "name")),
+        thumbnail,
+    })
+}
+
+impl QueuedPost {
+    // Videos are recognised by the filter. Anything else is only queued if
+    // it has an image or a gallery. Crossposts are queued if the original
+    // post would be. Comments are queued according to the mode.
+    pub fn from_object(value: JsValue, comments: CommentMode) ->
+        Option<Self>
+    {
+        if let Some(comment) = SavedComment::from_object(&value) {
+            return match comments {
+                CommentMode::Skip => None,
+                CommentMode::Card => Some(QueuedPost::Comment(comment)),
+                CommentMode::ParentMedia => comment.link_request()
+                    .map(|_| QueuedPost::CommentParent(comment)),
+            };
+        }
+
+        if let Some(original) = original_entry(&value) {
+            let data = Reflect::get(&value, &"data".into()).ok()?;
+            return Some(QueuedPost::Crosspost(Crosspost {
+                name: get_string(&data, "name")?,
+                title: get_string(&data, "title").unwrap_or_default(),
+                original: Box::new(Self::from_original(original)?),
+                facts: post_facts(&value),
+            }));
+        }
+        Self::from_original(value)
+    }
+
+    fn from_original(value: JsValue) -> Option<Self> {
+        match Post::from_object(value.clone()) {
+            Some(post) => Some(QueuedPost::Video(post, value)),
+            None => MediaPost::from_object(value).map(QueuedPost::Media),
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            QueuedPost::Video(post, _) => &post.title,
+            QueuedPost::Media(post) => &post.title,
+            QueuedPost::Crosspost(post) => &post.title,
+            QueuedPost::Comment(comment)
+                | QueuedPost::CommentParent(comment) =>
+                &comment.comment.link_title,
+        }
+    }
+
+    pub fn is_comment(&self) -> bool {
+        matches!(self, QueuedPost::Comment(_) | QueuedPost::CommentParent(_))
+    }
+
+    // The request for the post's media, for posts queued from a listing
+    // entry without the help of the filter.
+    pub fn media_request(&self, value: &JsValue) ->
+        Option<PostMediaRequest>
+    {
+        let request = match self {
+            QueuedPost::Comment(_) => None,
+            QueuedPost::CommentParent(comment) => comment.link_request(),
+            _ => media_request(&media_entry(value)),
+        };
+        request.map(|request| with_facts(request, &post_facts(value)))
+    }
+
+    // What the user's rules are checked against.
+    fn facts(&self) -> Option<PostFacts> {
+        match self {
+            QueuedPost::Video(_, value) => post_facts(value),
+            QueuedPost::Media(post) => post_facts(&post.data),
+            QueuedPost::Crosspost(post) => post.facts.clone(),
+            QueuedPost::Comment(comment)
+                | QueuedPost::CommentParent(comment) =>
+                post_facts(&comment.data),
+        }
+    }
+
+    // The post's media, with every source it's available from.
+    pub async fn get_media(&self) -> Result<MediaDescriptor, JsValue> {
+        let facts = self.facts();
+        match self {
+            QueuedPost::Crosspost(post) =>
+                post.original.get_own_media(&facts).await,
+            _ => self.get_own_media(&facts).await,
+        }
+    }
+
+    // The media of a post that isn't a crosspost.
+    async fn get_own_media(&self, facts: &Option<PostFacts>) ->
+        Result<MediaDescriptor, JsValue>
+    {
+        match self {
+            QueuedPost::Video(_, value) => {
+                let request = link_request(value)
+                    .ok_or_else(|| JsValue::from("malformed post"))?;
+                api::get_media(with_facts(request, facts)).await
+            },
+            QueuedPost::Media(post) => {
+                let request = media_request(&post.data)
+                    .ok_or_else(|| JsValue::from("malformed post"))?;
+                let link = link_request(&post.data)
+                    .filter(|link| {
+                        link.request.url != request.request.url
+                    });
+                let request = with_facts(request, facts);
+                match (api::get_media(request).await, link) {
+                    // The variant is gone, but the GIF may not be.
+                    (Err(_), Some(link)) =>
+                        api::get_media(with_facts(link, facts)).await,
+                    (result, _) => result,
+                }
+            },
+            QueuedPost::Crosspost(_) =>
+                Err(JsValue::from("crosspost of a crosspost")),
+            QueuedPost::Comment(_) =>
+                Err(JsValue::from("comments have no media")),
+            QueuedPost::CommentParent(comment) => {
+                let request = comment.link_request()
+                    .ok_or_else(|| JsValue::from("malformed comment"))?;
+                api::get_media(with_facts(request, facts)).await
+            },
+        }
+    }
+
+    pub async fn unsave(&self) -> Result<(), JsValue> {
+        match self {
+            QueuedPost::Video(post, _) => post.unsave().await,
+            QueuedPost::Media(post) => api::unsave(&post.name).await,
+            QueuedPost::Crosspost(post) => api::unsave(&post.name).await,
+            QueuedPost::Comment(comment)
+                | QueuedPost::CommentParent(comment) =>
+                api::unsave(&comment.comment.name).await,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,165 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            slideshow.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     A component showing an image, or each item of a gallery,
+//                  for a while.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use gloo_timers::callback::Timeout;
+use model::{MediaDescriptor, MediaKind};
+use yew::prelude::*;
+
+#[derive(PartialEq, Properties)]
+pub struct SlideshowProperties {
+    // An image, or a gallery
+    pub media: MediaDescriptor,
+    pub title: String,
+
+    // Seconds each slide is shown for
+    pub duration: u32,
+
+    // Emitted after the last slide has been shown.
+    pub onended: Callback<()>,
+
+    // Emitted instead of onended if the last slide fails to load.
+    pub onerror: Callback<()>,
+}
+
+pub enum SlideshowMessage {
+    Next,
+    Previous,
+    Failed,
+}
+
+pub struct Slideshow {
+    index: usize,
+
+    // Advances to the next slide. Dropping it cancels the timer.
+    timer: Option<Timeout>,
+}
+
+// The slides of the media: the items of a gallery, or else the media itself.
+fn slides(media: &MediaDescriptor) -> Vec<&MediaDescriptor> {
+    match media.kind {
+        MediaKind::Gallery if !media.items.is_empty() =>
+            media.items.iter().collect(),
+        _ => vec![media],
+    }
+}
+
+impl Slideshow {
+    fn start_timer(&mut self, context: &Context<Self>) {
+        let link = context.link().clone();
+        let milliseconds = context.props().duration.saturating_mul(1000);
+        self.timer = Some(Timeout::new(milliseconds, move || {
+            link.send_message(SlideshowMessage::Next);
+        }));
+    }
+
+    fn view_slide(&self, context: &Context<Self>, slide: &MediaDescriptor) ->
+        Html
+    {
+        let source = slide.primary().map(|source| source.url.clone())
+            .or_else(|| slide.poster.clone());
+
+        // Images that fail to load are skipped.
+        let onerror = context.link().callback(|_| SlideshowMessage::Failed);
+        let image = slide.primary()
+            .and_then(|source| source.mime_type.as_deref())
+            .map(|mime_type| mime_type.starts_with("image/"))
+            .unwrap_or(false);
+        match slide.kind {
+            MediaKind::Video | MediaKind::Gif if !image => html! {
+                <video class="media slide" autoplay=true muted=true
+                 loop=true playsinline=true poster={slide.poster.clone()}>
+                    { for slide.sources.iter().map(|source| html! {
+                        <source src={source.url.clone()}
+                         type={source.mime_type.clone()} />
+                    }) }
+                </video>
+            },
+            _ => html! {
+                <img class="media slide" src={source}
+                 alt={context.props().title.clone()} {onerror} />
+            },
+        }
+    }
+}
+
+impl Component for Slideshow {
+    type Message = SlideshowMessage;
+    type Properties = SlideshowProperties;
+
+    fn create(context: &Context<Self>) -> Self {
+        let mut slideshow = Self { index: 0, timer: None };
+        slideshow.start_timer(context);
+        slideshow
+    }
+
+    fn changed(&mut self, context: &Context<Self>,
+               _old_props: &Self::Properties) -> bool
+    {
+        self.index = 0;
+        self.start_timer(context);
+        true
+    }
+
+    fn update(&mut self, context: &Context<Self>, message: Self::Message) ->
+        bool
+    {
+        let count = slides(&context.props().media).len();
+        match message {
+            SlideshowMessage::Next if self.index + 1 >= count => {
+                self.timer = None;
+                context.props().onended.emit(());
+                false
+            },
+            // The post wasn't shown in full, so it hasn't ended.
+            SlideshowMessage::Failed if self.index + 1 >= count => {
+                self.timer = None;
+                context.props().onerror.emit(());
+                false
+            },
+            SlideshowMessage::Next | SlideshowMessage::Failed => {
+                self.index += 1;
+                self.start_timer(context);
+                true
+            },
+            SlideshowMessage::Previous => {
+                self.index = self.index.saturating_sub(1);
+                self.start_timer(context);
+                true
+            },
+        }
+    }
+
+    fn view(&self, context: &Context<Self>) -> Html {
+        use SlideshowMessage::*;
+        let media = &context.props().media;
+        let slides = slides(media);
+        let slide = slides[self.index.min(slides.len() - 1)];
+        html! {
+            <>
+                { self.view_slide(context, slide) }
+                <div class="slideshow-controls">
+                    <button onclick={context.link().callback(|_| Previous)}
+                     disabled={self.index == 0}>{"Previous"}</button>
+                    <span class="text">{
+                        format!("{} / {}", self.index + 1, slides.len())
+                    }</span>
+                    <button onclick={context.link().callback(|_| Next)}>{
+                        "Next"
+                    }</button>
+                </div>
+            </>
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -7,52 +7,139 @@
 //
 // CREATED:         06/13/2022
 //
-// LAST EDITED:     06/20/2022
+// LAST EDITED:     10/19/2026
 ////
 
+use model::{MediaDescriptor, MediaKind, MediaSource};
 use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::{JsFuture, spawn_local};
-use web_sys::HtmlVideoElement;
+use web_sys::{HtmlAudioElement, HtmlVideoElement};
 use yew::prelude::*;
-use crate::filter::Post;
+use crate::comment_card::CommentCard;
+use crate::post::{QueuedPost, SavedComment};
+use crate::slideshow::Slideshow;
+use crate::view::DistributedPost;
+
+// Seconds the audio track may drift from the video before it's resynced.
+const MAX_AUDIO_DRIFT: f64 = 0.3;
 
 #[derive(PartialEq, Properties)]
 pub struct VideoBoxProperties {
-    pub onended: Callback<Callback<Option<Post>>>,
+    pub onended: Callback<Callback<Option<DistributedPost>>>,
     pub unsave: bool,
+
+    // Seconds each image is shown for
+    pub slide_duration: u32,
+
+    // GIFs are looped until they've played for at least this many seconds,
+    // and at least this many times.
+    pub gif_min_duration: u32,
+    pub gif_min_loops: u32,
+
+    // Seconds each comment is shown for, and whether comments are unsaved
+    // along with posts
+    pub comment_duration: u32,
+    pub unsave_comments: bool,
 }
 
 pub enum VideoBoxMessage {
-    ReceivedVideoUrl(String),
+    ReceivedVideoUrl(MediaDescriptor),
     VideoEnded,
-    NewPost(Option<Post>),
+
+    // Media meant to be looped reached its end.
+    Looped(HtmlVideoElement),
+
+    // The post's media can't be played. Move on without unsaving it.
+    Skip,
+
+    // The viewer asked to see a post that was hidden behind its warning.
+    Reveal,
+    NewPost(Option<DistributedPost>),
 }
 
 #[derive(Default)]
 pub struct VideoBox {
-    post: Option<Post>,
-    url: Option<String>,
+    post: Option<QueuedPost>,
+    media: Option<MediaDescriptor>,
+
+    // Why the post is hidden until the viewer asks to see it, if it is.
+    // Its media isn't rendered until then, so nothing plays behind the blur.
+    warning: Option<&'static str>,
+    preview: Option<String>,
+
+    // Separate audio track, for media that has one.
+    audio: NodeRef,
+
+    // Times looping media has played through, and when it was received (in
+    // milliseconds since the epoch).
+    loops: u32,
+    started: f64,
+}
+
+// Make the audio element follow the state of the video element.
+fn sync_audio(audio: &HtmlAudioElement, video: &HtmlVideoElement) {
+    let drift = audio.current_time() - video.current_time();
+    if drift.abs() > MAX_AUDIO_DRIFT {
+        audio.set_current_time(video.current_time());
+    }
+    audio.set_muted(video.muted());
+    audio.set_volume(video.volume());
+    audio.set_playback_rate(video.playback_rate());
+
+    if video.paused() || video.ended() {
+        let _ = audio.pause();
+    } else if audio.paused() {
+        let _ = audio.play();
+    }
 }
 
 impl VideoBox {
+    fn audio_source(&self) -> Option<&MediaSource> {
+        self.media.as_ref().and_then(|media| media.audio.as_ref())
+    }
+
     fn fetch_video_url(&self, context: &Context<Self>) {
         use VideoBoxMessage::*;
         if let Some(post) = &self.post {
-            let link = context.link().callback(|url| ReceivedVideoUrl(url));
+            let link = context.link().clone();
             let post = post.clone();
             spawn_local(async move {
-                match post.get_url().await {
-                    Ok(url) => link.emit(url),
-                    Err(e) => web_sys::console::error_3(
-                        &e,
-                        &"while getting post video url".into(),
-                        &JsValue::from_serde(&post).unwrap()
-                    ),
+                match post.get_media().await {
+                    Ok(media) => link.send_message(ReceivedVideoUrl(media)),
+                    Err(e) => {
+                        web_sys::console::error_3(
+                            &e,
+                            &"while getting post video url".into(),
+                            &JsValue::from(post.title())
+                        );
+                        link.send_message(Skip);
+                    },
                 };
             });
         }
     }
 
+    // Move on to the next post, unsaving this one if we're asked to.
+    fn finish(&self, context: &Context<Self>) {
+        let props = context.props();
+        let post = self.post.as_ref().unwrap();
+        if props.unsave && (!post.is_comment() || props.unsave_comments) {
+            let post = post.clone();
+            spawn_local(async move {
+                post.unsave().await.unwrap();
+            });
+        }
+        self.fetch_next_post(context);
+    }
+
+    // Whether looping media has played for long enough to move on.
+    fn finished_looping(&self, context: &Context<Self>) -> bool {
+        let props = context.props();
+        let elapsed = js_sys::Date::now() - self.started;
+        self.loops >= props.gif_min_loops
+            && elapsed >= f64::from(props.gif_min_duration) * 1000.0
+    }
+
     fn fetch_next_post(&self, context: &Context<Self>) {
         use VideoBoxMessage::*;
         let callback = context.link().callback(|post| NewPost(post));
@@ -75,33 +162,109 @@ impl Component for VideoBox {
     {
         use VideoBoxMessage::*;
         match message {
-            ReceivedVideoUrl(url) => {
-                self.url = Some(url);
+            ReceivedVideoUrl(media) => {
+                self.media = Some(media);
+                self.loops = 0;
+                self.started = js_sys::Date::now();
                 true
             },
 
             VideoEnded => {
-                if context.props().unsave {
-                    let post = self.post.as_ref().unwrap().clone();
-                    spawn_local(async move {
-                        post.unsave().await.unwrap();
-                    });
+                self.finish(context);
+                true
+            },
+
+            Looped(video) => {
+                self.loops += 1;
+                if self.finished_looping(context) {
+                    self.finish(context);
+                    return true;
                 }
 
+                video.set_current_time(0.0);
+                let _ = video.play();
+                false
+            },
+
+            Skip => {
                 self.fetch_next_post(context);
                 true
             },
 
+            Reveal => {
+                self.warning = None;
+                self.started = js_sys::Date::now();
+                true
+            },
+
             NewPost(post) => {
-                self.url = None;
-                self.post = post;
-                self.fetch_video_url(context);
+                self.media = None;
+                self.warning = post.as_ref().and_then(|post| post.warning);
+                self.preview = post.as_ref()
+                    .and_then(|post| post.preview.clone());
+                self.post = post.map(|post| post.post);
+
+                // Comments shown as cards have no media to fetch.
+                if !matches!(self.post, Some(QueuedPost::Comment(_))) {
+                    self.fetch_video_url(context);
+                }
                 true
             },
         }
     }
 
     fn view(&self, context: &Context<Self>) -> Html {
+        let content = match (&self.post, &self.media) {
+            (Some(_), _) if self.warning.is_some() =>
+                self.view_warning(context),
+            (Some(QueuedPost::Comment(comment)), _) =>
+                self.view_comment(context, comment),
+            (Some(_), Some(media)) => self.view_media(context, media),
+            _ => return html! { <div class="player-window" /> },
+        };
+
+        // Titles can give away as much as the media, so they're hidden too.
+        let title = match self.warning {
+            Some(_) => "",
+            None => self.post.as_ref().unwrap().title(),
+        };
+        html! {
+            <div class="player-window">
+                <p class="text video-title">{ title }</p>
+                { content }
+            </div>
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+// Media views
+////
+
+impl VideoBox {
+    fn view_media(&self, context: &Context<Self>, media: &MediaDescriptor) ->
+        Html
+    {
+        // Animated images without a video rendition can't be told to loop
+        // a number of times, so they're shown for the minimum duration.
+        let image = media.primary()
+            .and_then(|source| source.mime_type.as_deref())
+            .map(|mime_type| mime_type.starts_with("image/"))
+            .unwrap_or(false);
+        match media.kind {
+            MediaKind::Gif if image => self.view_slideshow(
+                context, media, context.props().gif_min_duration),
+            MediaKind::Video | MediaKind::Gif =>
+                self.view_video(context, media),
+            MediaKind::Image | MediaKind::Gallery => self.view_slideshow(
+                context, media, context.props().slide_duration),
+            MediaKind::Embed => self.view_embed(context, media),
+        }
+    }
+
+    fn view_video(&self, context: &Context<Self>, media: &MediaDescriptor) ->
+        Html
+    {
         use VideoBoxMessage::*;
         let canplaythrough = |e: Event| {
             spawn_local (async move {
@@ -110,20 +273,116 @@ impl Component for VideoBox {
             });
         };
 
+        let audio = self.audio.clone();
+        let sync = Callback::from(move |e: Event| {
+            let video = e.target_dyn_into::<HtmlVideoElement>().unwrap();
+            if let Some(audio) = audio.cast::<HtmlAudioElement>() {
+                sync_audio(&audio, &video);
+            }
+        });
+
+        // The browser plays the first source it supports. Once the last one
+        // has failed, there's nothing left to try.
+        let last = media.sources.len().saturating_sub(1);
+        let sources = media.sources.iter().enumerate()
+            .map(|(index, source)| {
+                let onerror = (index == last)
+                    .then(|| context.link().callback(|_: Event| Skip));
+                html! {
+                    <source src={source.url.clone()}
+                     type={source.mime_type.clone()} {onerror} />
+                }
+            })
+            .collect::<Html>();
+
+        // GIFs are silent, so they can play inline without controls. Media
+        // meant to be looped is restarted until it's played for long enough.
+        let gif = media.kind == MediaKind::Gif;
+        let onended = match media.looping {
+            true => context.link().callback(|e: Event| {
+                Looped(e.target_dyn_into::<HtmlVideoElement>().unwrap())
+            }),
+            false => context.link().callback(|_| VideoEnded),
+        };
         html! {
-            <div class="player-window">
-                if let Some(url) = self.url.as_ref() {
-                    <p class="text video-title">{
-                        &self.post.as_ref().unwrap().title
-                    }</p>
-                    <video controls=true oncanplaythrough={canplaythrough}
-                     onended={context.link().callback(|_| VideoEnded)}>
-                        <source src={url.clone()} />
-                    </video>
+            <>
+                <video class={classes!("media", gif.then(|| "gif"))}
+                 controls={!gif} muted={gif} playsinline={gif}
+                 poster={media.poster.clone()}
+                 oncanplaythrough={canplaythrough}
+                 onplay={sync.clone()} onpause={sync.clone()}
+                 onseeked={sync.clone()} ontimeupdate={sync.clone()}
+                 onvolumechange={sync.clone()} onratechange={sync}
+                 {onended}>
+                    { sources }
+                </video>
+                if let Some(audio) = self.audio_source() {
+                    <audio ref={self.audio.clone()} preload="auto"
+                     src={audio.url.clone()} />
                 }
+            </>
+        }
+    }
+
+    fn view_slideshow(&self, context: &Context<Self>,
+                      media: &MediaDescriptor, duration: u32) -> Html
+    {
+        use VideoBoxMessage::*;
+        html! {
+            <Slideshow media={media.clone()}
+             title={self.post.as_ref().unwrap().title().to_string()}
+             {duration}
+             onended={context.link().callback(|_| VideoEnded)}
+             onerror={context.link().callback(|_| Skip)} />
+        }
+    }
+
+    fn view_comment(&self, context: &Context<Self>,
+                    comment: &SavedComment) -> Html
+    {
+        use VideoBoxMessage::*;
+        html! {
+            <CommentCard comment={comment.comment.clone()}
+             duration={context.props().comment_duration}
+             onended={context.link().callback(|_| VideoEnded)} />
+        }
+    }
+
+    // Reddit's blurred rendition of the post's preview, if it has one, with
+    // the reason it's hidden. Clicking anywhere on it shows the post.
+    fn view_warning(&self, context: &Context<Self>) -> Html {
+        use VideoBoxMessage::*;
+        html! {
+            <div class="sensitive-overlay"
+             onclick={context.link().callback(|_| Reveal)}>
+                if let Some(image) = self.preview.clone() {
+                    <img class="media sensitive-blur" src={image} />
+                }
+                <button class="sensitive-button">{
+                    format!("{}: click to show", self.warning.unwrap())
+                }</button>
             </div>
         }
     }
+
+    // Embeds don't tell us when they've ended, so they come with a button to
+    // move on.
+    fn view_embed(&self, context: &Context<Self>, media: &MediaDescriptor) ->
+        Html
+    {
+        use VideoBoxMessage::*;
+        html! {
+            <>
+                <iframe class="media embed"
+                 src={media.primary().map(|source| source.url.clone())}
+                 allowfullscreen=true />
+                <button class="next-button"
+                 onclick={context.link().callback(|_| VideoEnded)}>
+                    {"Next"}
+                </button>
+            </>
+        }
+    }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -7,26 +7,86 @@
 //
 // CREATED:         06/13/2022
 //
-// LAST EDITED:     06/21/2022
+// LAST EDITED:     10/19/2026
 ////
 
 use core::cmp::min;
 use std::collections::VecDeque;
 
 use js_sys::Array;
+use model::{
+    Filter, FilterRules, PostMediaRequest, PostOrder, QualityPreference,
+    SensitiveMode,
+};
+use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
-use crate::api::PostCollection;
-use crate::filter::Post;
+use crate::api::{prefetch, set_quality_preference};
+use crate::compilation::{Compilation, Interleaving, WeightedSource};
+use crate::post::{obscured_preview, post_facts, CommentMode, QueuedPost};
 use crate::video_box::VideoBox;
 
+// Number of queued posts whose media is resolved ahead of time.
+const PREFETCH_AHEAD: usize = 4;
+
+// Seconds each image is shown for, unless the user says otherwise.
+pub const DEFAULT_SLIDE_DURATION: u32 = 8;
+
+// GIFs are often only a second or two long, so by default they're looped
+// for a while before moving on.
+pub const DEFAULT_GIF_MIN_DURATION: u32 = 10;
+pub const DEFAULT_GIF_MIN_LOOPS: u32 = 1;
+
+// Seconds each comment is shown for, unless the user says otherwise.
+pub const DEFAULT_COMMENT_DURATION: u32 = 15;
+
 #[derive(Clone, Default, PartialEq)]
 pub struct ApplicationData {
-    // Username for requests to Reddit API
-    pub username: String,
+    // Listings the posts are played from, and how they're mixed
+    pub sources: Vec<WeightedSource>,
+    pub interleaving: Interleaving,
+
+    // Order the posts are played in, and the seed to shuffle them with
+    pub order: PostOrder,
+    pub seed: Option<u32>,
 
     // Debug mode
     pub debug: bool,
+
+    // Constraints on the video quality the backend picks
+    pub quality: QualityPreference,
+
+    // Seconds each image is shown for
+    pub slide_duration: u32,
+
+    // Seconds, and number of times, a GIF is played for at least
+    pub gif_min_duration: u32,
+    pub gif_min_loops: u32,
+
+    // What to do with saved comments, and whether to unsave them
+    pub comments: CommentMode,
+    pub comment_duration: u32,
+    pub unsave_comments: bool,
+
+    // Which posts are played at all
+    pub filter: FilterRules,
+
+    // Whether each clip is played once, however many times it was posted,
+    // and whether its copies are unsaved with it
+    pub skip_duplicates: bool,
+    pub unsave_duplicates: bool,
+}
+
+// A post as it's handed out to a player.
+#[derive(Clone, PartialEq)]
+pub struct DistributedPost {
+    pub post: QueuedPost,
+
+    // Why the post is hidden until the viewer asks to see it, if it is
+    pub warning: Option<&'static str>,
+
+    // What's shown in its place until then
+    pub preview: Option<String>,
 }
 
 #[derive(Clone, PartialEq, Properties)]
@@ -35,20 +95,52 @@ pub struct AppViewModel {
 }
 
 pub enum AppViewMessage {
-    ReceivedList((Array, PostCollection)),
-    VideoEnded(Callback<Option<Post>>),
+    ReceivedList((Array, Compilation)),
+    VideoEnded(Callback<Option<DistributedPost>>),
 }
 
 #[derive(Default)]
 pub struct AppView {
     // Mechanism to retrieve new posts
-    post_collection: Option<PostCollection>,
+    post_collection: Option<Compilation>,
 
     // List of filtered posts
-    post_list: Option<VecDeque<Post>>,
+    post_list: Option<VecDeque<DistributedPost>>,
+
+    // Media requests for the posts in post_list, in the same order. Taken
+    // once they've been prefetched.
+    requests: VecDeque<Option<PostMediaRequest>>,
 
     // List of children waiting for a post
-    wait_queue: VecDeque<Callback<Option<Post>>>,
+    wait_queue: VecDeque<Callback<Option<DistributedPost>>>,
+
+    // The user's filter rules, ready to be evaluated
+    filter: Option<Filter>,
+}
+
+// Only saved posts can be unsaved. Unsaving a post from one of the other
+// sources does nothing, unless it's been saved too.
+fn unsaves(data: &ApplicationData) -> bool {
+    !data.debug && data.sources.iter().any(|source| source.source.is_saved())
+}
+
+// The next batch of posts. If there aren't any to be had, that's the end of
+// the posts as far as the player is concerned.
+async fn next_posts(collection: &mut Compilation) -> Array {
+    collection.next().await.unwrap_or_else(|e| {
+        web_sys::console::error_2(&e, &"while fetching posts".into());
+        Array::new()
+    })
+}
+
+// The content warning for a post that's NSFW or a spoiler.
+fn content_warning(value: &JsValue) -> Option<&'static str> {
+    let facts = post_facts(value)?;
+    match (facts.nsfw, facts.spoiler) {
+        (true, _) => Some("NSFW"),
+        (false, true) => Some("Spoiler"),
+        (false, false) => None,
+    }
 }
 
 impl AppView {
@@ -59,18 +151,57 @@ impl AppView {
                 |(value, collection)| ReceivedList((value, collection)));
             let mut collection = collection.clone();
             spawn_local(async move {
-                let response = collection.next().await.unwrap();
+                let response = next_posts(&mut collection).await;
                 link.emit((response, collection));
             });
         }
     }
 
+    fn next_post(&mut self) -> Option<DistributedPost> {
+        self.requests.pop_front();
+        self.post_list.as_mut().unwrap().pop_front()
+    }
+
+    // Resolve the media of the next few posts in the background, so that
+    // they're ready to play when they come up.
+    fn prefetch(&mut self) {
+        let requests = self.requests.iter_mut()
+            .take(PREFETCH_AHEAD)
+            .filter_map(|request| request.take())
+            .collect::<Vec<_>>();
+        if requests.is_empty() {
+            return;
+        }
+
+        spawn_local(async move {
+            if let Err(e) = prefetch(requests).await {
+                web_sys::console::error_2(&e, &"while prefetching".into());
+            }
+        });
+    }
+
+    // Whether the user's filter rules let the post through.
+    fn is_allowed(&self, value: &JsValue) -> bool {
+        let (filter, facts) = match (&self.filter, post_facts(value)) {
+            (Some(filter), Some(facts)) => (filter, facts),
+            _ => return true,
+        };
+        let now = js_sys::Date::now() / 1000.0;
+        match filter.check(&facts, now) {
+            Ok(()) => true,
+            Err(reason) => {
+                web_sys::console::debug_1(&reason.to_string().into());
+                false
+            },
+        }
+    }
+
     fn wake_wait_queue(&mut self) {
-        let post_list = self.post_list.as_mut().unwrap();
-        let posts = min(post_list.len(), self.wait_queue.len());
+        let posts = min(self.post_list.as_ref().unwrap().len(),
+                        self.wait_queue.len());
         for _ in 0..posts {
             let callback = self.wait_queue.pop_front().unwrap();
-            callback.emit(post_list.pop_front());
+            callback.emit(self.next_post());
         }
     }
 }
@@ -83,14 +214,34 @@ impl Component for AppView {
         use AppViewMessage::*;
         let link = context.link()
             .callback(|(value, collection)| ReceivedList((value, collection)));
-        let username = context.props().data.username.clone();
+        let data = &context.props().data;
+        let sources = data.sources.clone();
+        let interleaving = data.interleaving;
+        let (order, seed) = (data.order, data.seed);
+        let skip_duplicates = data.skip_duplicates;
+        let unsave_copies = data.unsave_duplicates && unsaves(data);
+        set_quality_preference(data.quality.clone());
         spawn_local(async move {
-            let mut collection = PostCollection::new(&username);
-            let response = collection.next().await.unwrap();
+            let mut collection = Compilation::new(
+                sources, interleaving, order, seed);
+            if skip_duplicates {
+                collection = collection.without_duplicates(unsave_copies);
+            }
+            let response = next_posts(&mut collection).await;
             link.emit((response, collection));
         });
 
-        Self::default()
+        // The form doesn't start the application with rules that don't
+        // compile, so this only fails if the rules came from elsewhere.
+        let filter = match Filter::new(data.filter.clone()) {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                web_sys::console::error_2(&e.to_string().into(),
+                                          &"in filter rules".into());
+                None
+            },
+        };
+        Self { filter, ..Self::default() }
     }
 
     fn update(&mut self, context: &Context<Self>, message: Self::Message) ->
@@ -101,22 +252,48 @@ impl Component for AppView {
             ReceivedList((array, collection)) => {
                 self.post_collection = Some(collection);
                 let mut post_list = VecDeque::new();
+                self.requests.clear();
+                let comments = context.props().data.comments;
+                let sensitive = context.props().data.filter.sensitive;
+                let exhausted = array.length() == 0;
                 for value in array.values() {
                     let value = value.unwrap();
-                    if let Some(post) = Post::from_object(value) {
-                        post_list.push_back(post);
+                    if !self.is_allowed(&value) {
+                        continue;
+                    }
+                    let warning = match (sensitive, content_warning(&value)) {
+                        (SensitiveMode::Skip, Some(_)) => continue,
+                        (SensitiveMode::Blur, warning) => warning,
+                        _ => None,
+                    };
+                    let post = QueuedPost::from_object(value.clone(),
+                                                       comments);
+                    let preview = warning
+                        .and_then(|_| obscured_preview(&value));
+                    if let Some(post) = post {
+                        self.requests.push_back(post.media_request(&value));
+                        post_list.push_back(DistributedPost {
+                            post, warning, preview,
+                        });
                     }
                 }
 
                 self.post_list = Some(post_list);
                 self.wake_wait_queue();
+                self.prefetch();
+
+                // Every post of the page may have been filtered out.
+                if !self.wait_queue.is_empty() && !exhausted {
+                    self.update_collection(context);
+                }
                 true
             },
 
             VideoEnded(callback) => {
-                let option = self.post_list.as_mut().unwrap().pop_front();
+                let option = self.next_post();
                 if let Some(post) = option {
                     callback.emit(Some(post));
+                    self.prefetch();
                 } else {
                     self.wait_queue.push_back(callback);
                     self.update_collection(context);
@@ -131,14 +308,32 @@ impl Component for AppView {
         let first_loop = context.link().callback(|c| VideoEnded(c));
         let second_loop = context.link().callback(|c| VideoEnded(c));
 
-        let unsave = !context.props().data.debug;
+        let data = &context.props().data;
+        let unsave = unsaves(data);
+        let slide_duration = data.slide_duration;
+        let gif_min_duration = data.gif_min_duration;
+        let gif_min_loops = data.gif_min_loops;
+        let comment_duration = data.comment_duration;
+        let unsave_comments = data.unsave_comments;
+        let seed = self.post_collection.as_ref().and_then(Compilation::seed);
         html! {
             if let Some(_) = &self.post_list {
                 <main>
                     <div class="video-player">
-                        <VideoBox onended={first_loop} unsave={unsave} />
-                        <VideoBox onended={second_loop} unsave={unsave} />
+                        <VideoBox onended={first_loop} unsave={unsave}
+                         {slide_duration} {gif_min_duration}
+                         {gif_min_loops} {comment_duration}
+                         {unsave_comments} />
+                        <VideoBox onended={second_loop} unsave={unsave}
+                         {slide_duration} {gif_min_duration}
+                         {gif_min_loops} {comment_duration}
+                         {unsave_comments} />
                     </div>
+                    if let Some(seed) = seed {
+                        <p class="text">{
+                            format!("Shuffled with seed {}", seed)
+                        }</p>
+                    }
                 </main>
             }
         }
@@ -0,0 +1,143 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            identity.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Telling posts of the same clip apart from different clips.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::fmt;
+
+use serde::{
+    de::{self, Deserializer, Visitor}, Serialize, Serializer, Deserialize,
+};
+
+// Crossposts of crossposts are followed back to the original this far, at
+// most.
+pub const MAX_CROSSPOST_DEPTH: usize = 8;
+
+// Thumbnails whose hashes differ in at most this many bits are taken to be
+// of the same clip.
+pub const MAX_HASH_DISTANCE: u32 = 5;
+
+// Hashes with fewer bits set than this (or more unset) are of thumbnails
+// that are nearly flat, e.g. black frames, which every clip could have.
+const MIN_HASH_DETAIL: u32 = 8;
+
+// What the backend is told about a post to identify its media.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdentityRequest {
+    // Fullname of the post, e.g. "t3_abc123"
+    pub name: String,
+
+    // The URL the post links to
+    pub url: Option<String>,
+
+    // Fullname of the post it was crossposted from, following crossposts
+    // of crossposts back to the original
+    pub crosspost_parent: Option<String>,
+
+    // URL of a small rendition of the post's preview image
+    pub thumbnail: Option<String>,
+}
+
+// Perceptual hash of a thumbnail: similar images have similar hashes. It's
+// serialized as a hex string, since JavaScript numbers can't hold 64 bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThumbnailHash(pub u64);
+
+// Everything two posts are compared by to tell whether they're of the same
+// clip.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MediaIdentity {
+    pub name: String,
+
+    // The post's URL with everything that doesn't change the media removed
+    pub url: Option<String>,
+
+    // Fullname of the original post, which is the post itself unless it's
+    // a crosspost
+    pub origin: String,
+    pub thumbnail: Option<ThumbnailHash>,
+}
+
+impl ThumbnailHash {
+    // Number of bits the hashes differ in.
+    pub fn distance(&self, other: &ThumbnailHash) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+
+    // Whether the thumbnail has enough detail for its hash to mean anything.
+    pub fn is_distinctive(&self) -> bool {
+        let ones = self.0.count_ones();
+        (MIN_HASH_DETAIL..=64 - MIN_HASH_DETAIL).contains(&ones)
+    }
+}
+
+impl Serialize for ThumbnailHash {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer
+    {
+        serializer.serialize_str(&format!("{:016x}", self.0))
+    }
+}
+
+struct ThumbnailHashVisitor;
+
+impl<'de> Visitor<'de> for ThumbnailHashVisitor {
+    type Value = ThumbnailHash;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a 64-bit hash as a hex string")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<ThumbnailHash, E>
+    where E: de::Error
+    {
+        u64::from_str_radix(value, 16)
+            .map(ThumbnailHash)
+            .map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for ThumbnailHash {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de>
+    {
+        deserializer.deserialize_str(ThumbnailHashVisitor)
+    }
+}
+
+impl MediaIdentity {
+    // Whether the two posts are certainly of the same clip: they're
+    // crossposts of the same post, or they link to the same media.
+    pub fn is_copy_of(&self, other: &MediaIdentity) -> bool {
+        if self.origin == other.origin {
+            return true;
+        }
+        match (&self.url, &other.url) {
+            (Some(url), Some(other)) => url == other,
+            _ => false,
+        }
+    }
+
+    // Whether the two posts are of the same clip: they're copies, or their
+    // thumbnails look the same.
+    pub fn is_duplicate_of(&self, other: &MediaIdentity) -> bool {
+        if self.is_copy_of(other) {
+            return true;
+        }
+        match (self.thumbnail, other.thumbnail) {
+            (Some(hash), Some(other)) => hash.is_distinctive()
+                && other.is_distinctive()
+                && hash.distance(&other) <= MAX_HASH_DISTANCE,
+            _ => false,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
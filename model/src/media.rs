@@ -0,0 +1,209 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            media.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Description of the media behind a post.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use serde::{Serialize, Deserialize};
+
+// What the media is, which determines how it's presented.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    #[default]
+    Video,
+
+    // A short, silent clip (including animated GIFs), played on a loop.
+    Gif,
+    Image,
+
+    // Several pieces of media, in `items`.
+    Gallery,
+
+    // A page from the media host, to be shown in an iframe.
+    Embed,
+}
+
+// One way of getting at the media, e.g. one resolution of a video.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MediaSource {
+    pub url: String,
+    pub mime_type: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+
+    // Bits per second
+    pub bandwidth: Option<u64>,
+    pub codecs: Option<String>,
+}
+
+// Response of the /video endpoint.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MediaDescriptor {
+    pub kind: MediaKind,
+
+    // Available sources, best quality first.
+    pub sources: Vec<MediaSource>,
+
+    // Audio track to be played alongside the sources, for hosts that serve
+    // video and audio separately. It's always a plain file, since it's
+    // played in an <audio> element.
+    pub audio: Option<MediaSource>,
+
+    // Seconds
+    pub duration: Option<f64>,
+
+    // Image to show before the media has loaded.
+    pub poster: Option<String>,
+
+    // The contents of a gallery, in order.
+    #[serde(default)]
+    pub items: Vec<MediaDescriptor>,
+
+    #[serde(default)]
+    pub nsfw: bool,
+
+    #[serde(default)]
+    pub spoiler: bool,
+
+    // Meant to be played on a loop, like a GIF, rather than once.
+    #[serde(default)]
+    pub looping: bool,
+}
+
+// Request of the /video endpoints: the post's link, and what its listing
+// entry says about the media behind it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PostMediaRequest {
+    #[serde(flatten)]
+    pub request: crate::MediaUrlRequest,
+
+    // Reddit transcodes GIFs uploaded to it to silent videos, which only
+    // the post tells apart from other videos.
+    #[serde(default)]
+    pub is_gif: bool,
+
+    // What the user's filter rules are checked against. For a crosspost,
+    // these are the crosspost's, not the original's.
+    #[serde(default)]
+    pub facts: Option<crate::PostFacts>,
+}
+
+impl From<crate::MediaUrlRequest> for PostMediaRequest {
+    fn from(request: crate::MediaUrlRequest) -> Self {
+        Self { request, is_gif: false, facts: None }
+    }
+}
+
+// One entry of the /video/batch response, in the order of the request.
+// Exactly one of media and error is set.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MediaResult {
+    // URL of the post, as it was in the request.
+    pub url: String,
+    pub media: Option<MediaDescriptor>,
+    pub error: Option<String>,
+}
+
+// Constraints on the source chosen for playback. Sent as query parameters of
+// the /video request.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QualityPreference {
+    pub max_height: Option<u32>,
+
+    // Bits per second
+    pub max_bandwidth: Option<u64>,
+
+    // Prefer sources with sound over higher quality sources without.
+    #[serde(default)]
+    pub prefer_audio: bool,
+}
+
+impl MediaKind {
+    // Guess the kind of media from its MIME type.
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        match mime_type.split_once('/')? {
+            ("image", "gif") => Some(MediaKind::Gif),
+            ("image", _) => Some(MediaKind::Image),
+            ("video", _) => Some(MediaKind::Video),
+            ("text", "html") => Some(MediaKind::Embed),
+            _ => None,
+        }
+    }
+}
+
+// Codec identifiers (RFC 6381) of the audio codecs browsers can play.
+const AUDIO_CODECS: &[&'static str] = &["mp4a", "opus", "vorbis", "flac"];
+
+impl MediaSource {
+    pub fn new(url: String) -> Self {
+        Self { url, ..Default::default() }
+    }
+
+    // Whether the source carries an audio stream, if its codecs are known.
+    pub fn has_audio(&self) -> Option<bool> {
+        self.codecs.as_ref().map(|codecs| {
+            codecs.split(',').map(|codec| codec.trim()).any(|codec| {
+                AUDIO_CODECS.iter().any(|audio| codec.starts_with(audio))
+            })
+        })
+    }
+
+    fn satisfies(&self, preference: &QualityPreference) -> bool {
+        let height_ok = match (self.height, preference.max_height) {
+            (Some(height), Some(max_height)) => height <= max_height,
+            _ => true,
+        };
+        let bandwidth_ok = match (self.bandwidth, preference.max_bandwidth) {
+            (Some(bandwidth), Some(max_bandwidth)) =>
+                bandwidth <= max_bandwidth,
+            _ => true,
+        };
+        height_ok && bandwidth_ok
+    }
+}
+
+impl MediaDescriptor {
+    // Media that's available from exactly one URL.
+    pub fn from_url(url: String) -> Self {
+        Self {
+            sources: vec![MediaSource::new(url)],
+            ..Default::default()
+        }
+    }
+
+    // The source that should be tried first. For a gallery, that's the
+    // primary source of its first item.
+    pub fn primary(&self) -> Option<&MediaSource> {
+        self.sources.first()
+            .or_else(|| self.items.iter().find_map(|item| item.primary()))
+    }
+
+    // Reorder the sources so that the best one satisfying the preference
+    // comes first. Sources that exceed the limits are kept as fallbacks,
+    // smallest first, so there's always something to play.
+    pub fn select(&mut self, preference: &QualityPreference) {
+        for item in self.items.iter_mut() {
+            item.select(preference);
+        }
+
+        let (mut preferred, mut fallback): (Vec<_>, Vec<_>) =
+            self.sources.drain(..)
+            .partition(|source| source.satisfies(preference));
+        if preference.prefer_audio && self.audio.is_none() {
+            // Stable, so the order is otherwise unchanged.
+            preferred.sort_by_key(|source| source.has_audio() == Some(false));
+        }
+        fallback.reverse();
+        preferred.append(&mut fallback);
+        self.sources = preferred;
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,57 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            ordering.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Orders the posts of a compilation can be played in.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use serde::{Serialize, Deserialize};
+use crate::ListingSource;
+
+// Most listings one compilation may draw from. In any order but Reddit's,
+// every post of each one is fetched up front.
+pub const MAX_SOURCES: usize = 10;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostOrder {
+    // The order Reddit lists them in, e.g. newest saved first
+    #[default]
+    Listing,
+
+    // Reddit's order, backwards, e.g. oldest saved first
+    Reversed,
+
+    // Random, but the same for the same seed
+    Shuffle,
+
+    // Highest score first
+    Score,
+
+    // Alphabetically by subreddit, in Reddit's order within each
+    Subreddit,
+
+    // Shortest first. Posts whose length isn't listed come last.
+    Duration,
+}
+
+// Everything the backend needs to put a compilation in order. Every post of
+// the sources is fetched before the first one is handed out, so orders other
+// than PostOrder::Listing are only as long as Reddit lets listings be.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrderedRequest {
+    pub sources: Vec<ListingSource>,
+    pub order: PostOrder,
+
+    // For PostOrder::Shuffle. The backend picks one if it's absent. It's
+    // kept to 32 bits so that it survives being a JavaScript number.
+    #[serde(default)]
+    pub seed: Option<u32>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
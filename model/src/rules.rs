@@ -0,0 +1,299 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            rules.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     User-configurable rules deciding which posts are played.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use std::fmt;
+
+use regex::{Regex, RegexBuilder};
+use serde::{Serialize, Deserialize};
+use crate::MediaDescriptor;
+
+// What to do with posts that have a flag, such as NSFW.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagRule {
+    #[default]
+    Allow,
+    Exclude,
+
+    // Only play posts that have the flag.
+    Only,
+}
+
+// How posts that are NSFW or spoilers are played.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensitiveMode {
+    #[default]
+    Show,
+
+    // Hidden behind a blur until the viewer asks to see them
+    Blur,
+    Skip,
+}
+
+// The rules, as the user configures them. Empty lists and absent limits
+// don't filter anything.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterRules {
+    // Subreddits, with or without the "r/"
+    pub include_subreddits: Vec<String>,
+    pub exclude_subreddits: Vec<String>,
+
+    // Domains of the linked media. Subdomains are included.
+    pub include_domains: Vec<String>,
+    pub exclude_domains: Vec<String>,
+
+    // Flair text, matched in full
+    pub include_flair: Vec<String>,
+    pub exclude_flair: Vec<String>,
+
+    pub nsfw: FlagRule,
+    pub spoilers: FlagRule,
+
+    // Applies to posts the rules above let through
+    pub sensitive: SensitiveMode,
+    pub min_score: Option<i64>,
+
+    // Seconds
+    pub min_duration: Option<f64>,
+    pub max_duration: Option<f64>,
+
+    // Regular expressions, matched case-insensitively against the title
+    pub title_pattern: Option<String>,
+    pub exclude_title_pattern: Option<String>,
+
+    // Days since the post was submitted
+    pub max_age: Option<f64>,
+}
+
+// What's known about a post from its listing entry.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PostFacts {
+    pub subreddit: Option<String>,
+    pub domain: Option<String>,
+    pub flair: Option<String>,
+    pub nsfw: bool,
+    pub spoiler: bool,
+    pub score: Option<i64>,
+
+    // Seconds, for the few hosts that tell us in the listing
+    pub duration: Option<f64>,
+    pub title: String,
+
+    // Seconds since the epoch
+    pub created: Option<f64>,
+}
+
+// Why a post was filtered out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rejection {
+    Subreddit(String),
+    Domain(String),
+    Flair(String),
+    Nsfw,
+    Spoiler,
+    Score(i64),
+    Duration(f64),
+    Title,
+    Age(f64),
+}
+
+// Rules that are ready to be evaluated.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    rules: FilterRules,
+    title: Option<Regex>,
+    exclude_title: Option<Regex>,
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Subreddit(subreddit) =>
+                write!(f, "filtered subreddit r/{}", subreddit),
+            Rejection::Domain(domain) =>
+                write!(f, "filtered domain {}", domain),
+            Rejection::Flair(flair) => write!(f, "filtered flair {}", flair),
+            Rejection::Nsfw => write!(f, "filtered NSFW setting"),
+            Rejection::Spoiler => write!(f, "filtered spoiler setting"),
+            Rejection::Score(score) => write!(f, "score {} too low", score),
+            Rejection::Duration(duration) =>
+                write!(f, "duration {}s out of range", duration),
+            Rejection::Title => write!(f, "filtered title"),
+            Rejection::Age(days) => write!(f, "{:.1} days old", days),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+fn compile(pattern: &Option<String>) -> Result<Option<Regex>, regex::Error> {
+    match pattern.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(pattern) => RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(Some),
+    }
+}
+
+fn normalize(name: &str) -> String {
+    let name = name.trim().trim_start_matches('/').to_lowercase();
+    match name.strip_prefix("r/") {
+        Some(name) => name.to_string(),
+        None => name,
+    }
+}
+
+fn matches_subreddit(names: &[String], subreddit: &str) -> bool {
+    let subreddit = normalize(subreddit);
+    names.iter().any(|name| normalize(name) == subreddit)
+}
+
+fn matches_domain(domains: &[String], domain: &str) -> bool {
+    let domain = domain.to_lowercase();
+    domains.iter().map(|name| name.trim().to_lowercase()).any(|name| {
+        domain == name || domain.ends_with(&(".".to_string() + &name))
+    })
+}
+
+fn matches_flair(flairs: &[String], flair: &str) -> bool {
+    flairs.iter().any(|name| name.trim().eq_ignore_ascii_case(flair.trim()))
+}
+
+// Whether a value passes the include and exclude lists. Values we don't know
+// only fail if there's a list they'd have to be included in.
+fn check_lists(include: &[String], exclude: &[String], value: Option<&str>,
+               matches: fn(&[String], &str) -> bool) -> bool
+{
+    match value {
+        Some(value) => (include.is_empty() || matches(include, value))
+            && !matches(exclude, value),
+        None => include.is_empty(),
+    }
+}
+
+fn check_flag(rule: FlagRule, flag: bool) -> bool {
+    match rule {
+        FlagRule::Allow => true,
+        FlagRule::Exclude => !flag,
+        FlagRule::Only => flag,
+    }
+}
+
+impl Filter {
+    pub fn new(rules: FilterRules) -> Result<Self, regex::Error> {
+        Ok(Self {
+            title: compile(&rules.title_pattern)?,
+            exclude_title: compile(&rules.exclude_title_pattern)?,
+            rules,
+        })
+    }
+
+    pub fn rules(&self) -> &FilterRules {
+        &self.rules
+    }
+
+    fn check_duration(&self, duration: Option<f64>) -> Result<(), Rejection> {
+        let duration = match duration {
+            Some(duration) => duration,
+            None => return Ok(()),
+        };
+        let too_short = self.rules.min_duration
+            .map(|min| duration < min).unwrap_or(false);
+        let too_long = self.rules.max_duration
+            .map(|max| duration > max).unwrap_or(false);
+        match too_short || too_long {
+            true => Err(Rejection::Duration(duration)),
+            false => Ok(()),
+        }
+    }
+
+    fn check_flags(&self, nsfw: bool, spoiler: bool) -> Result<(), Rejection> {
+        let skip = self.rules.sensitive == SensitiveMode::Skip;
+        if !check_flag(self.rules.nsfw, nsfw) || (skip && nsfw) {
+            return Err(Rejection::Nsfw);
+        }
+        if !check_flag(self.rules.spoilers, spoiler) || (skip && spoiler) {
+            return Err(Rejection::Spoiler);
+        }
+        Ok(())
+    }
+
+    // Check what's known of a post from its listing entry. The time is in
+    // seconds since the epoch.
+    pub fn check(&self, post: &PostFacts, now: f64) -> Result<(), Rejection> {
+        let rules = &self.rules;
+        let subreddit = post.subreddit.as_deref();
+        if !check_lists(&rules.include_subreddits, &rules.exclude_subreddits,
+                        subreddit, matches_subreddit) {
+            return Err(Rejection::Subreddit(
+                subreddit.unwrap_or_default().to_string()));
+        }
+        let domain = post.domain.as_deref();
+        if !check_lists(&rules.include_domains, &rules.exclude_domains,
+                        domain, matches_domain) {
+            return Err(Rejection::Domain(
+                domain.unwrap_or_default().to_string()));
+        }
+        let flair = post.flair.as_deref();
+        if !check_lists(&rules.include_flair, &rules.exclude_flair, flair,
+                        matches_flair) {
+            return Err(Rejection::Flair(
+                flair.unwrap_or_default().to_string()));
+        }
+
+        self.check_flags(post.nsfw, post.spoiler)?;
+        if let (Some(min), Some(score)) = (rules.min_score, post.score) {
+            if score < min {
+                return Err(Rejection::Score(score));
+            }
+        }
+        self.check_duration(post.duration)?;
+
+        let title_matches = self.title.as_ref()
+            .map(|title| title.is_match(&post.title)).unwrap_or(true);
+        let title_excluded = self.exclude_title.as_ref()
+            .map(|title| title.is_match(&post.title)).unwrap_or(false);
+        if !title_matches || title_excluded {
+            return Err(Rejection::Title);
+        }
+
+        if let (Some(max), Some(created)) = (rules.max_age, post.created) {
+            let days = (now - created) / 86400.0;
+            if days > max {
+                return Err(Rejection::Age(days));
+            }
+        }
+        Ok(())
+    }
+
+    // Check what's only known once the media has been extracted. Most hosts
+    // don't flag their media, so flags can only exclude it here.
+    pub fn check_media(&self, media: &MediaDescriptor) ->
+        Result<(), Rejection>
+    {
+        let skip = self.rules.sensitive == SensitiveMode::Skip;
+        if (self.rules.nsfw == FlagRule::Exclude || skip) && media.nsfw {
+            return Err(Rejection::Nsfw);
+        }
+        if (self.rules.spoilers == FlagRule::Exclude || skip)
+            && media.spoiler
+        {
+            return Err(Rejection::Spoiler);
+        }
+        self.check_duration(media.duration)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,230 @@
+///////////////////////////////////////////////////////////////////////////////
+// NAME:            source.rs
+//
+// AUTHOR:          Ethan D. Twardy <ethan.twardy@gmail.com>
+//
+// DESCRIPTION:     Reddit listings that posts can be played from, and the
+//                  entries in them that aren't posts.
+//
+// CREATED:         10/19/2026
+//
+// LAST EDITED:     10/19/2026
+////
+
+use serde::{Serialize, Deserialize};
+
+// Order of the posts in a listing. Not every listing supports every order:
+// see ListingSource::path and ListingSource::query.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListingSort {
+    #[default]
+    Hot,
+    New,
+    Top,
+    Rising,
+
+    // Search results only
+    Relevance,
+}
+
+// Period that "top" (and search results) are taken from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeRange {
+    Hour,
+    Day,
+    #[default]
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ListingSource {
+    // Posts and comments the user has saved
+    Saved { username: String },
+
+    // Posts the user has upvoted
+    Upvoted { username: String },
+
+    Subreddit { subreddit: String, sort: ListingSort, time: TimeRange },
+
+    // A custom feed, e.g. /user/someone/m/funny
+    Multireddit {
+        username: String, name: String, sort: ListingSort, time: TimeRange,
+    },
+
+    // Posts a user has submitted
+    Submitted { username: String, sort: ListingSort, time: TimeRange },
+
+    // Posts matching the query, optionally within one subreddit
+    Search {
+        query: String, subreddit: Option<String>, sort: ListingSort,
+        time: TimeRange,
+    },
+}
+
+// A saved comment, from the data of its listing entry (kind "t1").
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    // Fullname, e.g. "t1_abc123"
+    pub name: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub score: i64,
+
+    // Markdown, HTML-escaped as it is in listings
+    #[serde(default)]
+    pub body: String,
+
+    // The post the comment was left on, and what that post links to
+    #[serde(default)]
+    pub link_title: String,
+    pub link_permalink: Option<String>,
+    pub link_url: Option<String>,
+}
+
+impl Default for ListingSource {
+    fn default() -> Self {
+        ListingSource::Saved { username: String::new() }
+    }
+}
+
+impl ListingSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListingSort::Hot => "hot",
+            ListingSort::New => "new",
+            ListingSort::Top => "top",
+            ListingSort::Rising => "rising",
+            ListingSort::Relevance => "relevance",
+        }
+    }
+
+    // The order for a subreddit or multireddit, which can't be searched.
+    fn for_listing(&self) -> Self {
+        match self {
+            ListingSort::Relevance => ListingSort::Hot,
+            sort => *sort,
+        }
+    }
+}
+
+impl TimeRange {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeRange::Hour => "hour",
+            TimeRange::Day => "day",
+            TimeRange::Week => "week",
+            TimeRange::Month => "month",
+            TimeRange::Year => "year",
+            TimeRange::All => "all",
+        }
+    }
+}
+
+// Reddit usernames and subreddits may be given with their prefix, e.g.
+// "u/someone" or "/r/videos".
+fn strip_prefix(name: &str) -> &str {
+    name.trim().trim_matches('/').rsplit('/').next().unwrap_or("")
+}
+
+// The time range only applies to some orders.
+fn time_query(sort: ListingSort, time: TimeRange) ->
+    Vec<(&'static str, String)>
+{
+    match sort {
+        ListingSort::Top | ListingSort::Relevance =>
+            vec![("t", time.as_str().to_string())],
+        _ => Vec::new(),
+    }
+}
+
+impl ListingSource {
+    // Path of the listing in the Reddit API, e.g. "/r/videos/top".
+    pub fn path(&self) -> String {
+        match self {
+            ListingSource::Saved { username } =>
+                format!("/user/{}/saved", strip_prefix(username)),
+            ListingSource::Upvoted { username } =>
+                format!("/user/{}/upvoted", strip_prefix(username)),
+            ListingSource::Subreddit { subreddit, sort, .. } =>
+                format!("/r/{}/{}", strip_prefix(subreddit),
+                        sort.for_listing().as_str()),
+            ListingSource::Multireddit { username, name, sort, .. } =>
+                format!("/user/{}/m/{}/{}", strip_prefix(username),
+                        strip_prefix(name), sort.for_listing().as_str()),
+            ListingSource::Submitted { username, .. } =>
+                format!("/user/{}/submitted", strip_prefix(username)),
+            ListingSource::Search { subreddit: Some(subreddit), .. } =>
+                format!("/r/{}/search", strip_prefix(subreddit)),
+            ListingSource::Search { subreddit: None, .. } =>
+                "/search".to_string(),
+        }
+    }
+
+    // The names that path puts in the listing's path, as it puts them.
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            ListingSource::Saved { username }
+                | ListingSource::Upvoted { username }
+                | ListingSource::Submitted { username, .. } =>
+                vec![strip_prefix(username)],
+            ListingSource::Subreddit { subreddit, .. }
+                | ListingSource::Search { subreddit: Some(subreddit), .. } =>
+                vec![strip_prefix(subreddit)],
+            ListingSource::Multireddit { username, name, .. } =>
+                vec![strip_prefix(username), strip_prefix(name)],
+            ListingSource::Search { subreddit: None, .. } => Vec::new(),
+        }
+    }
+
+    // Query parameters of the listing, besides those for paging through
+    // it. Values aren't escaped.
+    pub fn query(&self) -> Vec<(&'static str, String)> {
+        match self {
+            ListingSource::Saved { .. } | ListingSource::Upvoted { .. } =>
+                Vec::new(),
+            ListingSource::Subreddit { sort, time, .. }
+                | ListingSource::Multireddit { sort, time, .. } =>
+                time_query(sort.for_listing(), *time),
+            ListingSource::Submitted { sort, time, .. } => {
+                let sort = match sort {
+                    ListingSort::Rising | ListingSort::Relevance =>
+                        ListingSort::Hot,
+                    sort => *sort,
+                };
+                let mut query = vec![("sort", sort.as_str().to_string())];
+                query.extend(time_query(sort, *time));
+                query
+            },
+            ListingSource::Search { query: terms, subreddit, sort, time } => {
+                let sort = match sort {
+                    ListingSort::Rising => ListingSort::Hot,
+                    sort => *sort,
+                };
+                let mut query = vec![
+                    ("q", terms.clone()),
+                    ("sort", sort.as_str().to_string()),
+                    ("type", "link".to_string()),
+                ];
+                if subreddit.is_some() {
+                    query.push(("restrict_sr", "true".to_string()));
+                }
+                query.extend(time_query(sort, *time));
+                query
+            },
+        }
+    }
+
+    // Only saved items can be unsaved.
+    pub fn is_saved(&self) -> bool {
+        matches!(self, ListingSource::Saved { .. })
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////